@@ -1,41 +1,24 @@
-use aoc2020::Vector2;
-use aoc2020::{parse_gridmap, GridMap};
+use aoc2020::parse_grid;
 
-fn trees_in_slope(grid: &GridMap, slope: Vector2) -> u64 {
-    let mut pos = Vector2::new(0, 0);
-
-    let mut tree_count = 0;
-    while pos.y < grid.rows {
-        pos += slope;
-        if pos.x >= grid.cols {
-            pos.x = pos.x % grid.cols;
-        }
-        if let Some(&c) = grid.data.get(&pos) {
-            if c == '#' {
-                tree_count += 1;
-            }
-        }
-    }
-    tree_count
-}
+use super::slopes::{Forest, Slope};
 
 pub fn part1(input: &str) -> u64 {
-    let grid = parse_gridmap(input);
-    trees_in_slope(&grid, Vector2::new(3, 1))
+    let forest = Forest::new(parse_grid(input));
+    forest.count(Slope::new(3, 1)) as u64
 }
 
 pub fn part2(input: &str) -> u64 {
-    let grid = parse_gridmap(input);
+    let forest = Forest::new(parse_grid(input));
     let slopes = [
-        Vector2::new(1, 1),
-        Vector2::new(3, 1),
-        Vector2::new(5, 1),
-        Vector2::new(7, 1),
-        Vector2::new(1, 2),
+        Slope::new(1, 1),
+        Slope::new(3, 1),
+        Slope::new(5, 1),
+        Slope::new(7, 1),
+        Slope::new(1, 2),
     ];
     slopes
         .iter()
-        .map(|&slope| trees_in_slope(&grid, slope))
+        .map(|&slope| forest.count(slope) as u64)
         .product()
 }
 
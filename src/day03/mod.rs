@@ -1,4 +1,5 @@
 mod day03;
+mod slopes;
 
 pub use day03::part1;
 pub use day03::part2;
@@ -0,0 +1,217 @@
+use std::ops::RangeInclusive;
+
+use aoc2020::number_theory::{gcd, lcm};
+use aoc2020::{Grid, Vector2};
+
+// how far the toboggan goes right and down between two stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Slope {
+    pub right: i64,
+    pub down: i64,
+}
+
+impl Slope {
+    pub fn new(right: i64, down: i64) -> Self {
+        assert!(down > 0, "the toboggan only goes down");
+        Slope { right, down }
+    }
+
+    // right / down as a line instead of a step, in lowest terms so the toboggan
+    // stops at every cell the line crosses exactly: right 2 down 4 stops every
+    // 2 rows, like right 1 down 2
+    pub fn line(right: i64, down: i64) -> Self {
+        assert!(down > 0, "the toboggan only goes down");
+        let divisor = gcd(right as i128, down as i128) as i64;
+        Slope::new(right / divisor, down / divisor)
+    }
+}
+
+pub struct Forest {
+    pub grid: Grid,
+    // going past the bottom starts over at the top, until the toboggan is back
+    // where it started
+    pub vertical_wrap: bool,
+}
+
+impl Forest {
+    pub fn new(grid: Grid) -> Self {
+        Forest {
+            grid,
+            vertical_wrap: false,
+        }
+    }
+
+    pub fn with_vertical_wrap(mut self) -> Self {
+        self.vertical_wrap = true;
+        self
+    }
+
+    // every cell the toboggan stops at, not counting the start
+    pub fn path(&self, slope: Slope) -> Vec<Vector2> {
+        let (cols, rows) = (self.grid.cols, self.grid.rows);
+        let stops = if self.vertical_wrap {
+            // steps until both coordinates come back to 0
+            let across = cols / gcd(slope.right as i128, cols as i128) as i64;
+            let along = rows / gcd(slope.down as i128, rows as i128) as i64;
+            lcm(across as i128, along as i128).unwrap() as i64 - 1
+        } else {
+            (rows - 1) / slope.down
+        };
+
+        (1..=stops)
+            .map(|step| {
+                Vector2::new(
+                    (step * slope.right).rem_euclid(cols),
+                    (step * slope.down).rem_euclid(rows),
+                )
+            })
+            .collect()
+    }
+
+    pub fn trees(&self, slope: Slope) -> Vec<Vector2> {
+        self.path(slope)
+            .into_iter()
+            .filter(|pos| self.grid.get(pos) == '#')
+            .collect()
+    }
+
+    pub fn count(&self, slope: Slope) -> usize {
+        self.trees(slope).len()
+    }
+
+    // the slope hitting the least trees out of every right / down in the ranges,
+    // the first one found on a tie. Downs below 1 aren't slopes and are skipped
+    pub fn least_trees(
+        &self,
+        rights: RangeInclusive<i64>,
+        downs: RangeInclusive<i64>,
+    ) -> Option<(Slope, usize)> {
+        let mut best: Option<(Slope, usize)> = None;
+        for down in downs.filter(|&down| down > 0) {
+            for right in rights.clone() {
+                let slope = Slope::new(right, down);
+                let count = self.count(slope);
+                if best.is_none_or(|(_, least)| count < least) {
+                    best = Some((slope, count));
+                }
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Forest, Slope};
+    use aoc2020::{parse_grid, parse_gridmap, GridMap, Vector2};
+
+    const EXAMPLE: &str = "..##.......
+#...#...#..
+.#....#..#.
+..#.#...#.#
+.#...##..#.
+..#.##.....
+.#.#.#....#
+.#........#
+#.##...#...
+#...##....#
+.#..#...#.#";
+
+    #[test]
+    fn example() {
+        let forest = Forest::new(parse_grid(EXAMPLE));
+        let counts: Vec<usize> = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .map(|&(right, down)| forest.count(Slope::new(right, down)))
+            .collect();
+        assert_eq!(counts, vec![2, 7, 3, 4, 2]);
+
+        let trees = forest.trees(Slope::new(3, 1));
+        assert_eq!(trees[0], Vector2::new(6, 2));
+        assert!(trees.iter().all(|pos| forest.grid.get(pos) == '#'));
+    }
+
+    // trees_in_slope from before Forest, the reference for integer slopes
+    fn trees_in_slope(grid: &GridMap, slope: Vector2) -> u64 {
+        let mut pos = Vector2::new(0, 0);
+
+        let mut tree_count = 0;
+        while pos.y < grid.rows {
+            pos += slope;
+            if pos.x >= grid.cols {
+                pos.x %= grid.cols;
+            }
+            if let Some(&c) = grid.data.get(&pos) {
+                if c == '#' {
+                    tree_count += 1;
+                }
+            }
+        }
+        tree_count
+    }
+
+    #[test]
+    fn steps() {
+        for input in [EXAMPLE, include_str!("input")].iter() {
+            let forest = Forest::new(parse_grid(input));
+            let grid = parse_gridmap(input);
+            for &(right, down) in [(2, 2), (4, 2), (3, 3), (6, 4), (1, 2), (3, 1)].iter() {
+                assert_eq!(
+                    forest.count(Slope::new(right, down)) as u64,
+                    trees_in_slope(&grid, Vector2::new(right, down)),
+                    "right {} down {}",
+                    right,
+                    down
+                );
+            }
+        }
+        // right 2 down 2 skips every other row
+        let forest = Forest::new(parse_grid(EXAMPLE));
+        assert_eq!(forest.path(Slope::new(2, 2)).len(), 5);
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(Slope::line(2, 4), Slope::new(1, 2));
+        assert_eq!(Slope::line(-3, 6), Slope::new(-1, 2));
+        assert_eq!(Slope::line(0, 5), Slope::new(0, 1));
+        assert_ne!(Slope::new(2, 4), Slope::new(1, 2));
+        let forest = Forest::new(parse_grid(EXAMPLE));
+        assert_eq!(forest.path(Slope::line(2, 2)).len(), 10);
+        // right 1 down 3 stops at rows 3, 6 and 9
+        assert_eq!(
+            forest.path(Slope::new(1, 3)),
+            vec![Vector2::new(1, 3), Vector2::new(2, 6), Vector2::new(3, 9)]
+        );
+        // going left wraps around the other side
+        assert_eq!(forest.path(Slope::new(-1, 1))[0], Vector2::new(10, 1));
+    }
+
+    #[test]
+    fn vertical_wrap() {
+        let grid = parse_grid("#.\n..\n.#");
+        let forest = Forest::new(grid.clone());
+        assert_eq!(forest.count(Slope::new(1, 1)), 0);
+
+        // 6 steps to get back to the start, passing the tree at (1, 2) on the way
+        let forest = Forest::new(grid).with_vertical_wrap();
+        assert_eq!(forest.path(Slope::new(1, 1)).len(), 5);
+        assert_eq!(forest.trees(Slope::new(1, 1)), vec![Vector2::new(1, 2)]);
+    }
+
+    #[test]
+    fn least_trees() {
+        let forest = Forest::new(parse_grid(EXAMPLE));
+        let (slope, count) = forest.least_trees(1..=7, 1..=2).unwrap();
+        let brute = (1..=2)
+            .flat_map(|down| (1..=7).map(move |right| (right, down)))
+            .map(|(right, down)| forest.count(Slope::new(right, down)))
+            .min()
+            .unwrap();
+        assert_eq!(count, brute);
+        assert_eq!(forest.count(slope), count);
+
+        assert_eq!(forest.least_trees(1..=7, -1..=2), Some((slope, count)));
+        assert_eq!(forest.least_trees(1..=7, -3..=0), None);
+    }
+}
@@ -1,4 +1,5 @@
 mod day04;
+mod schema;
 
 pub use day04::part1;
 pub use day04::part2;
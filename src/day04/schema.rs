@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// what a field's value has to look like, bounds are inclusive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Any,
+    Range { min: i64, max: i64 },
+    // a number followed by one of the units, each with its own range
+    Units(Vec<(String, i64, i64)>),
+    // '#' and 6 hex digits
+    Colour,
+    OneOf(Vec<String>),
+    // exactly this many digits, leading zeros included
+    Digits(usize),
+}
+
+impl Rule {
+    // false for a range, or a unit's range, that nothing fits in
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Rule::Range { min, max } => min <= max,
+            Rule::Units(units) => units.iter().all(|(_, min, max)| min <= max),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    Missing,
+    NotANumber,
+    OutOfRange(i64),
+    UnknownUnit,
+    NotAColour,
+    NotAllowed,
+    Digits { expected: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub field: String,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.field)?;
+        match &self.reason {
+            Reason::Missing => write!(f, "missing"),
+            Reason::NotANumber => write!(f, "not a number"),
+            Reason::OutOfRange(value) => write!(f, "{} is out of range", value),
+            Reason::UnknownUnit => write!(f, "unknown unit"),
+            Reason::NotAColour => write!(f, "not a colour"),
+            Reason::NotAllowed => write!(f, "not one of the allowed values"),
+            Reason::Digits { expected } => write!(f, "should be {} digits", expected),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    Io(io::ErrorKind),
+    Syntax { line_number: usize },
+    UnknownRule { line_number: usize, rule: String },
+    // a range with min > max
+    EmptyRange { line_number: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Schema { fields: Vec::new() }
+    }
+
+    // panics on a rule that isn't valid, schemas from a file get an error instead
+    pub fn required(mut self, name: &str, rule: Rule) -> Self {
+        assert!(rule.is_valid(), "{} has an empty range", name);
+        self.fields.push(Field {
+            name: name.to_string(),
+            required: true,
+            rule,
+        });
+        self
+    }
+
+    pub fn optional(mut self, name: &str, rule: Rule) -> Self {
+        assert!(rule.is_valid(), "{} has an empty range", name);
+        self.fields.push(Field {
+            name: name.to_string(),
+            required: false,
+            rule,
+        });
+        self
+    }
+
+    // the rules from part 2, the same as the schema file next to this one
+    pub fn passport() -> Self {
+        let words = |words: &[&str]| words.iter().map(|w| w.to_string()).collect();
+        Schema::new()
+            .required(
+                "byr",
+                Rule::Range {
+                    min: 1920,
+                    max: 2002,
+                },
+            )
+            .required(
+                "iyr",
+                Rule::Range {
+                    min: 2010,
+                    max: 2020,
+                },
+            )
+            .required(
+                "eyr",
+                Rule::Range {
+                    min: 2020,
+                    max: 2030,
+                },
+            )
+            .required(
+                "hgt",
+                Rule::Units(vec![
+                    ("cm".to_string(), 150, 193),
+                    ("in".to_string(), 59, 76),
+                ]),
+            )
+            .required("hcl", Rule::Colour)
+            .required(
+                "ecl",
+                Rule::OneOf(words(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth"])),
+            )
+            .required("pid", Rule::Digits(9))
+            .optional("cid", Rule::Any)
+    }
+
+    // one field per line: name, required or optional, the rule and its arguments.
+    // Blank lines and lines starting with '#' are skipped
+    pub fn parse(text: &str) -> Result<Schema, SchemaError> {
+        let mut schema = Schema::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line_number = idx + 1;
+            let syntax = SchemaError::Syntax { line_number };
+            let words: Vec<&str> = line.split_ascii_whitespace().collect();
+            if words.is_empty() || words[0].starts_with('#') {
+                continue;
+            }
+            if words.len() < 3 {
+                return Err(syntax);
+            }
+            let required = match words[1] {
+                "required" => true,
+                "optional" => false,
+                _ => return Err(syntax),
+            };
+            let args = &words[3..];
+            let numbers = |args: &[&str]| -> Result<Vec<i64>, SchemaError> {
+                args.iter()
+                    .map(|arg| arg.parse().map_err(|_| syntax.clone()))
+                    .collect()
+            };
+            let rule = match (words[2], args.len()) {
+                ("any", 0) => Rule::Any,
+                ("colour", 0) => Rule::Colour,
+                ("range", 2) => {
+                    let bounds = numbers(args)?;
+                    Rule::Range {
+                        min: bounds[0],
+                        max: bounds[1],
+                    }
+                }
+                ("units", n) if n > 0 && n % 3 == 0 => {
+                    let mut units = Vec::new();
+                    for unit in args.chunks(3) {
+                        let bounds = numbers(&unit[1..])?;
+                        units.push((unit[0].to_string(), bounds[0], bounds[1]));
+                    }
+                    Rule::Units(units)
+                }
+                ("oneof", n) if n > 0 => Rule::OneOf(args.iter().map(|s| s.to_string()).collect()),
+                ("digits", 1) => Rule::Digits(args[0].parse().map_err(|_| syntax.clone())?),
+                ("any", _)
+                | ("colour", _)
+                | ("range", _)
+                | ("units", _)
+                | ("oneof", _)
+                | ("digits", _) => return Err(syntax),
+                (rule, _) => {
+                    return Err(SchemaError::UnknownRule {
+                        line_number,
+                        rule: rule.to_string(),
+                    })
+                }
+            };
+            if !rule.is_valid() {
+                return Err(SchemaError::EmptyRange { line_number });
+            }
+            schema.fields.push(Field {
+                name: words[0].to_string(),
+                required,
+                rule,
+            });
+        }
+        Ok(schema)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Schema, SchemaError> {
+        let text = fs::read_to_string(path).map_err(|e| SchemaError::Io(e.kind()))?;
+        Schema::parse(&text)
+    }
+
+    // every field that failed, in schema order. Keys the schema doesn't know are ignored
+    pub fn validate(&self, passport: &HashMap<&str, &str>) -> Vec<Failure> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                let reason = match passport.get(field.name.as_str()) {
+                    Some(value) => check(&field.rule, value).err()?,
+                    None if field.required => Reason::Missing,
+                    None => return None,
+                };
+                Some(Failure {
+                    field: field.name.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+}
+
+fn in_range(value: &str, min: i64, max: i64) -> Result<(), Reason> {
+    let value: i64 = value.parse().map_err(|_| Reason::NotANumber)?;
+    if value >= min && value <= max {
+        Ok(())
+    } else {
+        Err(Reason::OutOfRange(value))
+    }
+}
+
+fn check(rule: &Rule, value: &str) -> Result<(), Reason> {
+    match rule {
+        Rule::Any => Ok(()),
+        Rule::Range { min, max } => in_range(value, *min, *max),
+        Rule::Units(units) => {
+            // the longest unit that fits, so "cm" isn't read as "m"
+            let (unit, min, max) = units
+                .iter()
+                .filter(|(unit, _, _)| value.ends_with(unit.as_str()))
+                .max_by_key(|(unit, _, _)| unit.len())
+                .ok_or(Reason::UnknownUnit)?;
+            in_range(&value[..value.len() - unit.len()], *min, *max)
+        }
+        Rule::Colour => match value.strip_prefix('#') {
+            Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+            _ => Err(Reason::NotAColour),
+        },
+        Rule::OneOf(allowed) => {
+            if allowed.iter().any(|a| a == value) {
+                Ok(())
+            } else {
+                Err(Reason::NotAllowed)
+            }
+        }
+        Rule::Digits(expected) => {
+            if value.len() == *expected && value.chars().all(|c| c.is_ascii_digit()) {
+                Ok(())
+            } else {
+                Err(Reason::Digits {
+                    expected: *expected,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Failure, Reason, Rule, Schema, SchemaError};
+    use std::collections::HashMap;
+
+    #[test]
+    fn file_matches_code() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/day04/schema");
+        assert_eq!(Schema::load(path), Ok(Schema::passport()));
+        assert_eq!(
+            Schema::load("no such schema"),
+            Err(SchemaError::Io(std::io::ErrorKind::NotFound))
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Schema::parse("byr required range 1920\n"),
+            Err(SchemaError::Syntax { line_number: 1 })
+        );
+        assert_eq!(
+            Schema::parse("# units\nhgt sometimes any"),
+            Err(SchemaError::Syntax { line_number: 2 })
+        );
+        assert_eq!(
+            Schema::parse("\nhgt required metres 1 2"),
+            Err(SchemaError::UnknownRule {
+                line_number: 2,
+                rule: "metres".to_string()
+            })
+        );
+        assert_eq!(
+            Schema::parse("byr required range 2002 1920"),
+            Err(SchemaError::EmptyRange { line_number: 1 })
+        );
+        assert_eq!(
+            Schema::parse("hgt required units cm 150 193 in 76 59"),
+            Err(SchemaError::EmptyRange { line_number: 1 })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_range_in_code() {
+        Schema::new().required("byr", Rule::Range { min: 2, max: 1 });
+    }
+
+    #[test]
+    fn failures() {
+        let schema = Schema::passport();
+        let passport: HashMap<&str, &str> = vec![
+            ("byr", "2003"),
+            ("iyr", "soon"),
+            ("eyr", "2025"),
+            ("hgt", "190"),
+            ("hcl", "#12345z"),
+            ("ecl", "wat"),
+            ("pid", "0123456789"),
+            ("xyz", "ignored"),
+        ]
+        .into_iter()
+        .collect();
+        let reasons: Vec<String> = schema
+            .validate(&passport)
+            .iter()
+            .map(|failure| failure.to_string())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "byr: 2003 is out of range",
+                "iyr: not a number",
+                "hgt: unknown unit",
+                "hcl: not a colour",
+                "ecl: not one of the allowed values",
+                "pid: should be 9 digits",
+            ]
+        );
+
+        // cid is optional, anything else can't be left out
+        let schema = Schema::new()
+            .required("hgt", Rule::Units(vec![("in".to_string(), 59, 76)]))
+            .optional("cid", Rule::Any);
+        let passport: HashMap<&str, &str> = HashMap::new();
+        assert_eq!(
+            schema.validate(&passport),
+            vec![Failure {
+                field: "hgt".to_string(),
+                reason: Reason::Missing
+            }]
+        );
+        let passport: HashMap<&str, &str> = vec![("hgt", "77in")].into_iter().collect();
+        assert_eq!(schema.validate(&passport)[0].reason, Reason::OutOfRange(77));
+
+        // "m" also ends "150cm", but "cm" is the longer match
+        let schema = Schema::parse("hgt required units m 1 2 cm 150 193").unwrap();
+        let passport: HashMap<&str, &str> = vec![("hgt", "150cm")].into_iter().collect();
+        assert!(schema.validate(&passport).is_empty());
+        let passport: HashMap<&str, &str> = vec![("hgt", "2m")].into_iter().collect();
+        assert!(schema.validate(&passport).is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Seat {
+    pub row: u32,
+    pub col: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PassError {
+    Length { expected: usize, found: usize },
+    // position is 0 based, F/B are expected before L/R
+    Character { position: usize, found: char },
+    OutOfRange(Seat),
+    Duplicate(Seat),
+}
+
+// a bad pass in a list of passes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line_number: usize,
+    pub error: PassError,
+}
+
+// both parts need at least a bit, and a seat id has to fit in a u32
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidWidths {
+    pub row_bits: u32,
+    pub col_bits: u32,
+}
+
+// boarding passes as binary, F/B for the row bits and L/R for the column bits.
+// The seat id, row * cols + col, is the whole pass read as a single number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codec {
+    row_bits: u32,
+    col_bits: u32,
+}
+
+impl Codec {
+    // 128 rows of 8 seats
+    pub const PLANE: Codec = Codec {
+        row_bits: 7,
+        col_bits: 3,
+    };
+
+    pub fn new(row_bits: u32, col_bits: u32) -> Result<Self, InvalidWidths> {
+        if row_bits == 0 || col_bits == 0 || row_bits + col_bits > 32 {
+            return Err(InvalidWidths { row_bits, col_bits });
+        }
+        Ok(Codec { row_bits, col_bits })
+    }
+
+    pub fn row_bits(&self) -> u32 {
+        self.row_bits
+    }
+
+    pub fn col_bits(&self) -> u32 {
+        self.col_bits
+    }
+
+    pub fn rows(&self) -> u32 {
+        1 << self.row_bits
+    }
+
+    pub fn cols(&self) -> u32 {
+        1 << self.col_bits
+    }
+
+    pub fn id(&self, seat: Seat) -> u32 {
+        seat.row << self.col_bits | seat.col
+    }
+
+    pub fn seat(&self, id: u32) -> Seat {
+        Seat {
+            row: id >> self.col_bits,
+            col: id & (self.cols() - 1),
+        }
+    }
+
+    pub fn decode(&self, pass: &str) -> Result<Seat, PassError> {
+        let expected = (self.row_bits + self.col_bits) as usize;
+        let found = pass.chars().count();
+        if found != expected {
+            return Err(PassError::Length { expected, found });
+        }
+
+        let mut id = 0;
+        for (position, c) in pass.chars().enumerate() {
+            let bit = match (position < self.row_bits as usize, c) {
+                (true, 'F') | (false, 'L') => 0,
+                (true, 'B') | (false, 'R') => 1,
+                _ => return Err(PassError::Character { position, found: c }),
+            };
+            id = id << 1 | bit;
+        }
+        Ok(self.seat(id))
+    }
+
+    pub fn encode(&self, seat: Seat) -> Result<String, PassError> {
+        if seat.row >= self.rows() || seat.col >= self.cols() {
+            return Err(PassError::OutOfRange(seat));
+        }
+        let bits = |value: u32, width: u32, zero: char, one: char| {
+            (0..width)
+                .rev()
+                .map(|bit| if value >> bit & 1 == 1 { one } else { zero })
+                .collect::<String>()
+        };
+        Ok(bits(seat.row, self.row_bits, 'F', 'B') + &bits(seat.col, self.col_bits, 'L', 'R'))
+    }
+
+    // the seat ids of every pass, one per line
+    pub fn read(&self, input: &str) -> Result<BTreeSet<u32>, LineError> {
+        let mut ids = BTreeSet::new();
+        for (idx, pass) in input.lines().enumerate() {
+            let fail = |error| LineError {
+                line_number: idx + 1,
+                error,
+            };
+            let seat = self.decode(pass).map_err(fail)?;
+            if !ids.insert(self.id(seat)) {
+                return Err(fail(PassError::Duplicate(seat)));
+            }
+        }
+        Ok(ids)
+    }
+
+    // the rows from the first to the last one with a taken seat, the rows in front
+    // of and behind those aren't on this plane
+    fn plane_rows(&self, taken: &BTreeSet<u32>) -> Option<(u32, u32)> {
+        let first = self.seat(*taken.iter().next()?).row;
+        let last = self.seat(*taken.iter().next_back()?).row;
+        Some((first, last))
+    }
+
+    // every free seat in the rows that are on the plane
+    pub fn empty_seats(&self, taken: &BTreeSet<u32>) -> Vec<Seat> {
+        match self.plane_rows(taken) {
+            Some((first, last)) => (first..=last)
+                .flat_map(|row| (0..self.cols()).map(move |col| Seat { row, col }))
+                .filter(|&seat| !taken.contains(&self.id(seat)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // a line per row: '#' taken, '.' free and ' ' for the seats that aren't on the plane
+    pub fn seat_map(&self, taken: &BTreeSet<u32>) -> String {
+        let rows = self.plane_rows(taken);
+        let on_plane = |row| rows.is_some_and(|(first, last)| first <= row && row <= last);
+        let width = (self.rows() - 1).to_string().len();
+
+        let mut map = String::new();
+        for row in 0..self.rows() {
+            let mut line = format!("{:>width$} ", row, width = width);
+            for col in 0..self.cols() {
+                let id = self.id(Seat { row, col });
+                line.push(if taken.contains(&id) {
+                    '#'
+                } else if on_plane(row) {
+                    '.'
+                } else {
+                    ' '
+                });
+            }
+            map += line.trim_end();
+            map.push('\n');
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Codec, InvalidWidths, LineError, PassError, Seat};
+
+    #[test]
+    fn examples() {
+        let codec = Codec::PLANE;
+        let passes = [
+            ("FBFBBFFRLR", 44, 5, 357),
+            ("BFFFBBFRRR", 70, 7, 567),
+            ("FFFBBBFRRR", 14, 7, 119),
+            ("BBFFBBFRLL", 102, 4, 820),
+        ];
+        for &(pass, row, col, id) in passes.iter() {
+            let seat = codec.decode(pass).unwrap();
+            assert_eq!(seat, Seat { row, col });
+            assert_eq!(codec.id(seat), id);
+            assert_eq!(codec.encode(seat).unwrap(), pass);
+        }
+    }
+
+    #[test]
+    fn widths() {
+        let codec = Codec::new(2, 1).unwrap();
+        assert_eq!(codec.decode("BFR"), Ok(Seat { row: 2, col: 1 }));
+        assert_eq!(codec.encode(Seat { row: 3, col: 0 }).unwrap(), "BBL");
+        assert_eq!(
+            codec.encode(Seat { row: 4, col: 0 }),
+            Err(PassError::OutOfRange(Seat { row: 4, col: 0 }))
+        );
+
+        let wide = Codec::new(16, 16).unwrap();
+        let seat = Seat {
+            row: 65535,
+            col: 65535,
+        };
+        assert_eq!(wide.id(seat), u32::MAX);
+        assert_eq!(wide.decode(&wide.encode(seat).unwrap()), Ok(seat));
+
+        for &(row_bits, col_bits) in [(0, 3), (7, 0), (20, 13), (32, 1)].iter() {
+            assert_eq!(
+                Codec::new(row_bits, col_bits),
+                Err(InvalidWidths { row_bits, col_bits })
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let codec = Codec::PLANE;
+        assert_eq!(
+            codec.decode("FBFBBFFRL"),
+            Err(PassError::Length {
+                expected: 10,
+                found: 9
+            })
+        );
+        // an L where a row bit should be
+        assert_eq!(
+            codec.decode("FBFBBFLRLR"),
+            Err(PassError::Character {
+                position: 6,
+                found: 'L'
+            })
+        );
+        assert_eq!(
+            codec.read("FBFBBFFRLR\nBFFFBBFRRR\nFBFBBFFRLR"),
+            Err(LineError {
+                line_number: 3,
+                error: PassError::Duplicate(Seat { row: 44, col: 5 })
+            })
+        );
+    }
+
+    #[test]
+    fn empty_seats_and_map() {
+        let codec = Codec::new(2, 2).unwrap();
+        // rows 1 and 2 are on the plane, the front one with its first seat free
+        let taken = codec.read("FBLR\nFBRL\nFBRR\nBFLR\nBFRL\nBFRR").unwrap();
+        let empty: Vec<u32> = codec
+            .empty_seats(&taken)
+            .into_iter()
+            .map(|seat| codec.id(seat))
+            .collect();
+        assert_eq!(empty, vec![4, 8]);
+
+        let taken = codec.read("FBLL\nFBRL\nFBRR\nBFRL").unwrap();
+        assert_eq!(codec.seat_map(&taken), "0\n1 #.##\n2 ..#.\n3\n");
+    }
+}
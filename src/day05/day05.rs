@@ -1,35 +1,21 @@
-use std::collections::BTreeSet;
-
-fn read_ids(input: &str) -> BTreeSet<u16> {
-    let mut ids = BTreeSet::new();
-
-    for line in input.lines() {
-        // id = col * 8 + row, which is the same as col << 3 + row
-        // we can just skip that and parse the entire line as u16
-        let id = line.chars().fold(0, |acc, elem| {
-            (acc << 1) + if elem == 'B' || elem == 'R' { 1 } else { 0 }
-        });
-        ids.insert(id);
-    }
-    ids
-}
+use super::codec::Codec;
 
 pub fn part1(input: &str) -> u16 {
-    let ids = read_ids(input);
-    *ids.iter().next_back().unwrap()
+    let ids = Codec::PLANE.read(input).unwrap();
+    *ids.iter().next_back().unwrap() as u16
 }
 
 pub fn part2(input: &str) -> u16 {
-    let ids = read_ids(input);
-    for id in ids.iter() {
-        // if the next id is not in the set and the next next id is, we found our gap
-        if !ids.contains(&(id + 1)) && ids.contains(&(id + 2)) {
-            return id + 1;
-        }
-    }
-    // We should return an Option<u16>, but input is well formed and there is always an answer
-    // so we should never reach this point, but panic if we do.
-    unreachable!();
+    let codec = Codec::PLANE;
+    let ids = codec.read(input).unwrap();
+    // ours is the only free seat with both neighbours taken
+    let seat = codec
+        .empty_seats(&ids)
+        .into_iter()
+        .map(|seat| codec.id(seat))
+        .find(|&id| id > 0 && ids.contains(&(id - 1)) && ids.contains(&(id + 1)))
+        .unwrap();
+    seat as u16
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+mod codec;
 mod day05;
 
 pub use day05::part1;
@@ -1,7 +1,11 @@
 use std::ops::RangeInclusive;
 
+use aoc2020::image::{self, Frames, Palette, Rgb, WHITE};
 use aoc2020::{parse_grid, Grid, Vector2};
 
+use super::frontier::Frontier;
+use super::seating::{Rules, Seating, Sight};
+
 // instead of iterating over every element in the grid, we search once for all seat indexes
 fn create_seats(grid: &Grid) -> Vec<usize> {
     let mut seats = Vec::new();
@@ -114,14 +118,19 @@ fn count_occupied(grid: &Grid) -> usize {
     occupied
 }
 
-pub fn part1(input: &str) -> usize {
+// the original step loop, Seating and Frontier have to agree with it
+fn stable_occupied(input: &str, eyesight: bool) -> usize {
     let mut grid = parse_grid(input);
     let mut next_grid = grid.clone();
-
     let seats = create_seats(&grid);
-    let adj = create_adjacent_neighbours(&grid, &seats);
+    let (adj, empty_range) = if eyesight {
+        (create_eyesight_neighbours(&grid, &seats), 5..=9)
+    } else {
+        (create_adjacent_neighbours(&grid, &seats), 4..=9)
+    };
+
     loop {
-        let changed = next(&grid, &seats, &adj, 4..=9, &mut next_grid);
+        let changed = next(&grid, &seats, &adj, empty_range.clone(), &mut next_grid);
         std::mem::swap(&mut grid, &mut next_grid);
         if !changed {
             break;
@@ -130,21 +139,34 @@ pub fn part1(input: &str) -> usize {
     count_occupied(&grid)
 }
 
+pub fn part1(input: &str) -> usize {
+    let seating = Seating::new(parse_grid(input), &Sight::ADJACENT, Rules::PART1);
+    let mut frontier = Frontier::new(&seating);
+    frontier.run();
+    frontier.occupied()
+}
+
 pub fn part2(input: &str) -> usize {
-    let mut grid = parse_grid(input);
-    let mut next_grid = grid.clone();
-    let seats = create_seats(&grid);
-    let adj = create_eyesight_neighbours(&grid, &seats);
+    let seating = Seating::new(parse_grid(input), &Sight::EYESIGHT, Rules::PART2);
+    let mut frontier = Frontier::new(&seating);
+    frontier.run();
+    frontier.occupied()
+}
 
-    loop {
-        let changed = next(&grid, &seats, &adj, 5..=9, &mut next_grid);
-        std::mem::swap(&mut grid, &mut next_grid);
-        if !changed {
-            break;
-        }
-    }
-    count_occupied(&grid)
+const EMPTY: Rgb = [40, 160, 40];
+const OCCUPIED: Rgb = [200, 30, 30];
+
+// every step of part 1 until the seats settle, empty seats green and occupied ones red
+pub fn frames(input: &str) -> Frames {
+    let palette = Palette::new(WHITE).with('L', EMPTY).with('#', OCCUPIED);
+    let mut seating = Seating::new(parse_grid(input), &Sight::ADJACENT, Rules::PART1);
+
+    let mut frames = Frames::new();
+    frames.push(image::from_grid(&seating.grid, &palette));
+    seating.run(|_, grid, _| frames.push(image::from_grid(grid, &palette)));
+    frames
 }
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -158,4 +180,41 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 2174);
     }
+
+    #[test]
+    fn same_as_next() {
+        let input = include_str!("input");
+        assert_eq!(super::part1(input), super::stable_occupied(input, false));
+        assert_eq!(super::part2(input), super::stable_occupied(input, true));
+    }
+
+    #[test]
+    fn frames() {
+        let example = concat!(
+            "L.LL.LL.LL\n",
+            "LLLLLLL.LL\n",
+            "L.L.L..L..\n",
+            "LLLL.LL.LL\n",
+            "L.LL.LL.LL\n",
+            "L.LLLLL.LL\n",
+            "..L.L.....\n",
+            "LLLLLLLLLL\n",
+            "L.LLLLLL.L\n",
+            "L.LLLLL.LL\n",
+        );
+        // the start and the 5 steps until the seats settle
+        let frames = super::frames(example);
+        assert_eq!(frames.images.len(), 6);
+        assert!(frames
+            .images
+            .iter()
+            .all(|image| (image.width, image.height) == (10, 10)));
+        let last = frames.images.last().unwrap();
+        let occupied = last
+            .pixels
+            .iter()
+            .filter(|&&p| p == super::OCCUPIED)
+            .count();
+        assert_eq!(occupied, 37);
+    }
 }
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use super::seating::{Outcome, Rules, Seating};
+
+fn get(bits: &[u64], seat: usize) -> bool {
+    bits[seat / 64] & (1 << (seat % 64)) != 0
+}
+
+fn toggle(bits: &mut [u64], seat: usize) {
+    bits[seat / 64] ^= 1 << (seat % 64);
+}
+
+// Seating, but a step only looks at seats that could change: the ones that changed
+// last step and the ones watching them. Occupied seats are bits, one buffer is read
+// while the other one is written
+pub struct Frontier {
+    rules: Rules,
+    neighbours: Vec<Vec<usize>>,
+    // the seats that have a seat as a neighbour, not always the same
+    // as its own neighbours for custom finders
+    watchers: Vec<Vec<usize>>,
+    current: Vec<u64>,
+    next: Vec<u64>,
+    frontier: Vec<usize>,
+    queued: Vec<u64>,
+    steps: usize,
+}
+
+impl Frontier {
+    // starts from the seats as they are now, every seat has to be looked at once
+    pub fn new(seating: &Seating) -> Self {
+        let neighbours = seating.neighbours().to_vec();
+        let total = neighbours.len();
+        let mut watchers = vec![Vec::new(); total];
+        for (seat, seen) in neighbours.iter().enumerate() {
+            for &other in seen {
+                watchers[other].push(seat);
+            }
+        }
+
+        let words = total.div_ceil(64);
+        let mut current = vec![0; words];
+        for (seat, &idx) in seating.seats().iter().enumerate() {
+            if seating.grid.data[idx] == '#' {
+                toggle(&mut current, seat);
+            }
+        }
+
+        Frontier {
+            rules: seating.rules,
+            neighbours,
+            watchers,
+            next: current.clone(),
+            current,
+            frontier: (0..total).collect(),
+            queued: vec![0; words],
+            steps: 0,
+        }
+    }
+
+    // how many seats changed
+    pub fn step(&mut self) -> usize {
+        self.next.copy_from_slice(&self.current);
+        let mut changed = Vec::new();
+        for &seat in self.frontier.iter() {
+            let n = self.neighbours[seat]
+                .iter()
+                .filter(|&&other| get(&self.current, other))
+                .count();
+            let flips = if get(&self.current, seat) {
+                n >= self.rules.tolerance
+            } else {
+                n <= self.rules.crowd
+            };
+            if flips {
+                toggle(&mut self.next, seat);
+                changed.push(seat);
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+
+        for &seat in self.frontier.iter() {
+            self.queued[seat / 64] = 0;
+        }
+        self.frontier.clear();
+        for &seat in changed.iter() {
+            for &other in std::iter::once(&seat).chain(self.watchers[seat].iter()) {
+                if !get(&self.queued, other) {
+                    toggle(&mut self.queued, other);
+                    self.frontier.push(other);
+                }
+            }
+        }
+
+        if !changed.is_empty() {
+            self.steps += 1;
+        }
+        changed.len()
+    }
+
+    // steps until nothing changes or the seats repeat
+    pub fn run(&mut self) -> Outcome {
+        let mut seen: HashMap<Vec<u64>, usize> = HashMap::new();
+        seen.insert(self.current.clone(), self.steps);
+        loop {
+            if self.step() == 0 {
+                return Outcome::Stable { steps: self.steps };
+            }
+            if let Some(first) = seen.insert(self.current.clone(), self.steps) {
+                return Outcome::Oscillating {
+                    first,
+                    period: self.steps - first,
+                };
+            }
+        }
+    }
+
+    pub fn occupied(&self) -> usize {
+        self.current
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Frontier;
+    use crate::day11::seating::{Rules, Seating, Sight};
+    use aoc2020::{parse_grid, Grid, Vector2};
+
+    #[test]
+    fn same_steps_as_seating() {
+        let input = include_str!("input");
+        let models = [
+            (Sight::ADJACENT, Rules::PART1),
+            (Sight::EYESIGHT, Rules::PART2),
+        ];
+        for (sight, rules) in models.iter() {
+            let mut seating = Seating::new(parse_grid(input), sight, *rules);
+            let mut frontier = Frontier::new(&seating);
+            loop {
+                let changed = seating.step().len();
+                assert_eq!(frontier.step(), changed);
+                assert_eq!(frontier.occupied(), seating.occupied());
+                if changed == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn one_way_neighbours() {
+        // the left seat watches the right one, but not the other way around
+        let right = |grid: &Grid, pos: Vector2| {
+            if pos.x == 0 {
+                vec![grid.index(&(pos + Vector2::new(1, 0)))]
+            } else {
+                vec![]
+            }
+        };
+        let rules = Rules {
+            crowd: 0,
+            tolerance: 1,
+        };
+        let mut seating = Seating::new(parse_grid("LL"), &right, rules);
+        let mut frontier = Frontier::new(&seating);
+        let outcome = frontier.run();
+        assert_eq!(outcome, seating.run(|_, _, _| {}));
+        assert_eq!(frontier.occupied(), seating.occupied());
+    }
+}
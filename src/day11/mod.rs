@@ -1,4 +1,6 @@
 mod day11;
+mod frontier;
+mod seating;
 
 pub use day11::part1;
 pub use day11::part2;
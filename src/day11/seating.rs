@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use aoc2020::{Grid, Vector2};
+
+pub const FOUR_WAY: [Vector2; 4] = [
+    Vector2::new(-1, 0),
+    Vector2::new(0, -1),
+    Vector2::new(0, 1),
+    Vector2::new(1, 0),
+];
+
+pub const EIGHT_WAY: [Vector2; 8] = [
+    Vector2::new(-1, -1),
+    Vector2::new(-1, 0),
+    Vector2::new(-1, 1),
+    Vector2::new(0, -1),
+    Vector2::new(0, 1),
+    Vector2::new(1, -1),
+    Vector2::new(1, 0),
+    Vector2::new(1, 1),
+];
+
+// which seats a seat looks at, as grid indices. Anything that isn't a seat is ignored
+pub trait NeighbourFinder {
+    fn find(&self, grid: &Grid, seat: Vector2) -> Vec<usize>;
+}
+
+impl<F: Fn(&Grid, Vector2) -> Vec<usize>> NeighbourFinder for F {
+    fn find(&self, grid: &Grid, seat: Vector2) -> Vec<usize> {
+        self(grid, seat)
+    }
+}
+
+// the first seat in each direction at most `range` cells away, or up to the
+// edge of the grid without a range
+pub struct Sight<'a> {
+    pub directions: &'a [Vector2],
+    pub range: Option<i64>,
+}
+
+impl Sight<'static> {
+    // part 1
+    pub const ADJACENT: Sight<'static> = Sight {
+        directions: &EIGHT_WAY,
+        range: Some(1),
+    };
+    // part 2
+    pub const EYESIGHT: Sight<'static> = Sight {
+        directions: &EIGHT_WAY,
+        range: None,
+    };
+}
+
+impl NeighbourFinder for Sight<'_> {
+    fn find(&self, grid: &Grid, seat: Vector2) -> Vec<usize> {
+        let mut result = Vec::new();
+        for &direction in self.directions {
+            let mut current = seat + direction;
+            let mut distance = 1;
+            while grid.in_bounds(&current) && self.range.is_none_or(|range| distance <= range) {
+                if grid.get(&current) != '.' {
+                    result.push(grid.index(&current));
+                    break;
+                }
+                current += direction;
+                distance += 1;
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    // an empty seat is taken when at most this many neighbours are occupied
+    pub crowd: usize,
+    // an occupied seat is left when at least this many neighbours are occupied
+    pub tolerance: usize,
+}
+
+impl Rules {
+    pub const PART1: Rules = Rules {
+        crowd: 0,
+        tolerance: 4,
+    };
+    pub const PART2: Rules = Rules {
+        crowd: 0,
+        tolerance: 5,
+    };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // nothing changes after this many steps
+    Stable { steps: usize },
+    // the seats after step `first` come back every `period` steps
+    Oscillating { first: usize, period: usize },
+}
+
+pub struct Seating {
+    pub grid: Grid,
+    pub rules: Rules,
+    // grid index of every seat
+    seats: Vec<usize>,
+    // neighbours of every seat, as positions in `seats`
+    neighbours: Vec<Vec<usize>>,
+    steps: usize,
+}
+
+impl Seating {
+    pub fn new(grid: Grid, finder: &dyn NeighbourFinder, rules: Rules) -> Self {
+        let seats: Vec<usize> = (0..grid.data.len())
+            .filter(|&idx| grid.data[idx] != '.')
+            .collect();
+        let seat_of: HashMap<usize, usize> = seats
+            .iter()
+            .enumerate()
+            .map(|(seat, &idx)| (idx, seat))
+            .collect();
+
+        let neighbours = seats
+            .iter()
+            .map(|&idx| {
+                let pos = Vector2::new(idx as i64 % grid.cols, idx as i64 / grid.cols);
+                finder
+                    .find(&grid, pos)
+                    .iter()
+                    .filter_map(|idx| seat_of.get(idx).cloned())
+                    .collect()
+            })
+            .collect();
+
+        Seating {
+            grid,
+            rules,
+            seats,
+            neighbours,
+            steps: 0,
+        }
+    }
+
+    fn is_occupied(&self, seat: usize) -> bool {
+        self.grid.data[self.seats[seat]] == '#'
+    }
+
+    // the grid indices of the seats that changed, a step that
+    // changes nothing isn't counted
+    pub fn step(&mut self) -> Vec<usize> {
+        let changed: Vec<usize> = (0..self.seats.len())
+            .filter(|&seat| {
+                let n = self.neighbours[seat]
+                    .iter()
+                    .filter(|&&other| self.is_occupied(other))
+                    .count();
+                if self.is_occupied(seat) {
+                    n >= self.rules.tolerance
+                } else {
+                    n <= self.rules.crowd
+                }
+            })
+            .map(|seat| self.seats[seat])
+            .collect();
+
+        for &idx in changed.iter() {
+            self.grid.data[idx] = if self.grid.data[idx] == '#' { 'L' } else { '#' };
+        }
+        if !changed.is_empty() {
+            self.steps += 1;
+        }
+        changed
+    }
+
+    // steps until nothing changes or the seats repeat, calling back with the step
+    // number, the grid and the seats that changed after every step
+    pub fn run<C: FnMut(usize, &Grid, &[usize])>(&mut self, mut callback: C) -> Outcome {
+        let mut seen: HashMap<Vec<u64>, usize> = HashMap::new();
+        seen.insert(self.packed(), self.steps);
+        loop {
+            let changed = self.step();
+            if changed.is_empty() {
+                return Outcome::Stable { steps: self.steps };
+            }
+            callback(self.steps, &self.grid, &changed);
+            if let Some(first) = seen.insert(self.packed(), self.steps) {
+                return Outcome::Oscillating {
+                    first,
+                    period: self.steps - first,
+                };
+            }
+        }
+    }
+
+    // one bit per seat, set when occupied
+    fn packed(&self) -> Vec<u64> {
+        let mut bits = vec![0; self.seats.len().div_ceil(64)];
+        for seat in 0..self.seats.len() {
+            if self.is_occupied(seat) {
+                bits[seat / 64] |= 1 << (seat % 64);
+            }
+        }
+        bits
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // neighbours of every seat, by position in seats()
+    pub fn neighbours(&self) -> &[Vec<usize>] {
+        &self.neighbours
+    }
+
+    // grid index of every seat
+    pub fn seats(&self) -> &[usize] {
+        &self.seats
+    }
+
+    pub fn occupied(&self) -> usize {
+        (0..self.seats.len())
+            .filter(|&seat| self.is_occupied(seat))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NeighbourFinder, Outcome, Rules, Seating, Sight, FOUR_WAY};
+    use aoc2020::{parse_grid, Grid, Vector2};
+
+    const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    #[test]
+    fn example() {
+        let mut seating = Seating::new(parse_grid(EXAMPLE), &Sight::ADJACENT, Rules::PART1);
+        let mut changes = Vec::new();
+        let outcome = seating.run(|step, _, changed| changes.push((step, changed.len())));
+        assert_eq!(outcome, Outcome::Stable { steps: 5 });
+        assert_eq!(seating.occupied(), 37);
+        // everyone sits down at first
+        assert_eq!(changes[0], (1, 71));
+
+        let mut seating = Seating::new(parse_grid(EXAMPLE), &Sight::EYESIGHT, Rules::PART2);
+        seating.run(|_, _, _| {});
+        assert_eq!(seating.occupied(), 26);
+    }
+
+    #[test]
+    fn finders() {
+        let grid = parse_grid("L.L..L\n......\nL.....");
+        let near = Sight {
+            directions: &FOUR_WAY,
+            range: Some(2),
+        };
+        let mut seen = near.find(&grid, Vector2::new(2, 0));
+        seen.sort_unstable();
+        // 5 is 3 cells away
+        assert_eq!(seen, vec![0]);
+        let seen = Sight::EYESIGHT.find(&grid, Vector2::new(2, 0));
+        assert_eq!(seen.len(), 3);
+
+        // a closure that only looks right
+        let right = |grid: &Grid, pos: Vector2| {
+            let next = pos + Vector2::new(1, 0);
+            if grid.in_bounds(&next) {
+                vec![grid.index(&next)]
+            } else {
+                vec![]
+            }
+        };
+        let mut seating = Seating::new(parse_grid("LL"), &right, Rules::PART1);
+        seating.step();
+        assert_eq!(seating.occupied(), 2);
+    }
+
+    #[test]
+    fn oscillation() {
+        // nobody stands having anyone around, and nobody sits next to someone
+        let rules = Rules {
+            crowd: 0,
+            tolerance: 1,
+        };
+        let mut seating = Seating::new(parse_grid("LL"), &Sight::ADJACENT, rules);
+        assert_eq!(
+            seating.run(|_, _, _| {}),
+            Outcome::Oscillating {
+                first: 0,
+                period: 2
+            }
+        );
+    }
+}
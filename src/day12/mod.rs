@@ -1,4 +1,5 @@
 mod day12;
+mod navigator;
 
 pub use day12::part1;
 pub use day12::part2;
@@ -0,0 +1,249 @@
+use aoc2020::Vector2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    // N, S, E and W, already turned into a direction times the value
+    Shift(Vector2),
+    // L and R in degrees, counter-clockwise is positive
+    Turn(i64),
+    Forward(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavigationError {
+    UnknownAction { line_number: usize, action: char },
+    BadValue { line_number: usize },
+    // a turn that isn't a multiple of 90 degrees
+    Angle { line_number: usize, degrees: i64 },
+}
+
+// what step returns for an Angle, before run knows which action it was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BadTurn(pub i64);
+
+pub fn parse_actions(input: &str) -> Result<Vec<Action>, NavigationError> {
+    let mut actions = Vec::new();
+    for (idx, line) in input.lines().enumerate() {
+        let line_number = idx + 1;
+        let mut chars = line.chars();
+        let action = chars
+            .next()
+            .ok_or(NavigationError::BadValue { line_number })?;
+        let value: i64 = chars
+            .as_str()
+            .parse()
+            .map_err(|_| NavigationError::BadValue { line_number })?;
+        let action = match action {
+            'N' => Action::Shift(Vector2::new(0, 1) * value),
+            'S' => Action::Shift(Vector2::new(0, -1) * value),
+            'E' => Action::Shift(Vector2::new(1, 0) * value),
+            'W' => Action::Shift(Vector2::new(-1, 0) * value),
+            'L' => Action::Turn(value),
+            'R' => Action::Turn(-value),
+            'F' => Action::Forward(value),
+            _ => {
+                return Err(NavigationError::UnknownAction {
+                    line_number,
+                    action,
+                })
+            }
+        };
+        actions.push(action);
+    }
+    Ok(actions)
+}
+
+// counter-clockwise by a number of quarter turns, exact for any vector
+pub fn rotate(vector: Vector2, quarters: i64) -> Vector2 {
+    match quarters.rem_euclid(4) {
+        0 => vector,
+        1 => Vector2::new(-vector.y, vector.x),
+        2 => Vector2::new(-vector.x, -vector.y),
+        _ => Vector2::new(vector.y, -vector.x),
+    }
+}
+
+// what N/S/E/W, L/R and F do to the ship
+pub trait Movement {
+    fn shift(&mut self, ship: &mut Vector2, offset: Vector2);
+    fn turn(&mut self, quarters: i64);
+    fn forward(&mut self, ship: &mut Vector2, times: i64);
+}
+
+// part 1: the ship moves itself and turns the way it's facing
+pub struct Heading {
+    pub direction: Vector2,
+}
+
+impl Movement for Heading {
+    fn shift(&mut self, ship: &mut Vector2, offset: Vector2) {
+        *ship += offset;
+    }
+
+    fn turn(&mut self, quarters: i64) {
+        self.direction = rotate(self.direction, quarters);
+    }
+
+    fn forward(&mut self, ship: &mut Vector2, times: i64) {
+        *ship += self.direction * times;
+    }
+}
+
+// part 2: everything but F moves the waypoint, relative to the ship
+pub struct Waypoint {
+    pub offset: Vector2,
+}
+
+impl Movement for Waypoint {
+    fn shift(&mut self, _ship: &mut Vector2, offset: Vector2) {
+        self.offset += offset;
+    }
+
+    fn turn(&mut self, quarters: i64) {
+        self.offset = rotate(self.offset, quarters);
+    }
+
+    fn forward(&mut self, ship: &mut Vector2, times: i64) {
+        *ship += self.offset * times;
+    }
+}
+
+pub struct Navigator<M> {
+    pub model: M,
+    pub position: Vector2,
+    // every position the ship has been at, starting at the origin
+    path: Vec<Vector2>,
+}
+
+impl<M: Movement> Navigator<M> {
+    pub fn new(model: M) -> Self {
+        Navigator {
+            model,
+            position: Vector2::new(0, 0),
+            path: vec![Vector2::new(0, 0)],
+        }
+    }
+
+    pub fn step(&mut self, action: Action) -> Result<(), BadTurn> {
+        match action {
+            Action::Shift(offset) => self.model.shift(&mut self.position, offset),
+            Action::Turn(degrees) if degrees % 90 == 0 => self.model.turn(degrees / 90),
+            Action::Turn(degrees) => return Err(BadTurn(degrees)),
+            Action::Forward(times) => self.model.forward(&mut self.position, times),
+        }
+        if *self.path.last().unwrap() != self.position {
+            self.path.push(self.position);
+        }
+        Ok(())
+    }
+
+    // stops at the first action that can't be done, the error's line number is
+    // the action's 1 based position in `actions`
+    pub fn run(&mut self, actions: &[Action]) -> Result<(), NavigationError> {
+        for (idx, &action) in actions.iter().enumerate() {
+            self.step(action)
+                .map_err(|BadTurn(degrees)| NavigationError::Angle {
+                    line_number: idx + 1,
+                    degrees,
+                })?;
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &[Vector2] {
+        &self.path
+    }
+
+    // manhattan distance from the origin
+    pub fn distance(&self) -> i64 {
+        self.position.x.abs() + self.position.y.abs()
+    }
+
+    // manhattan length of the whole path
+    pub fn travelled(&self) -> i64 {
+        self.path
+            .windows(2)
+            .map(|pair| (pair[1].x - pair[0].x).abs() + (pair[1].y - pair[0].y).abs())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Waypoint;
+    use super::{parse_actions, rotate, Action, BadTurn, Heading, NavigationError, Navigator};
+    use aoc2020::Vector2;
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    #[test]
+    fn rotation() {
+        let v = Vector2::new(10, 4);
+        assert_eq!(rotate(v, 1), Vector2::new(-4, 10));
+        assert_eq!(rotate(v, -1), Vector2::new(4, -10));
+        assert_eq!(rotate(v, 2), rotate(v, -2));
+        assert_eq!(rotate(v, 4), v);
+        assert_eq!(rotate(v, 7), rotate(v, -1));
+    }
+
+    #[test]
+    fn example() {
+        let actions = parse_actions(EXAMPLE).unwrap();
+
+        let mut ship = Navigator::new(Heading {
+            direction: Vector2::new(1, 0),
+        });
+        ship.run(&actions).unwrap();
+        assert_eq!(ship.distance(), 25);
+        // R90 doesn't move the ship, so it adds nothing to the path
+        assert_eq!(
+            ship.path(),
+            &[
+                Vector2::new(0, 0),
+                Vector2::new(10, 0),
+                Vector2::new(10, 3),
+                Vector2::new(17, 3),
+                Vector2::new(17, -8),
+            ]
+        );
+        assert_eq!(ship.travelled(), 10 + 3 + 7 + 11);
+
+        let mut ship = Navigator::new(Waypoint {
+            offset: Vector2::new(10, 1),
+        });
+        ship.run(&actions).unwrap();
+        assert_eq!(ship.distance(), 286);
+        assert_eq!(ship.model.offset, Vector2::new(4, -10));
+        assert_eq!(ship.path().len(), 4);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_actions("F10\nX3"),
+            Err(NavigationError::UnknownAction {
+                line_number: 2,
+                action: 'X'
+            })
+        );
+        assert_eq!(
+            parse_actions("F10\nN"),
+            Err(NavigationError::BadValue { line_number: 2 })
+        );
+
+        let actions = parse_actions("F10\nL45\nF1").unwrap();
+        assert_eq!(actions[1], Action::Turn(45));
+        let mut ship = Navigator::new(Heading {
+            direction: Vector2::new(1, 0),
+        });
+        assert_eq!(
+            ship.run(&actions),
+            Err(NavigationError::Angle {
+                line_number: 2,
+                degrees: 45
+            })
+        );
+        assert_eq!(ship.position, Vector2::new(10, 0));
+        assert_eq!(ship.step(Action::Turn(30)), Err(BadTurn(30)));
+    }
+}
@@ -1,21 +1,17 @@
-fn parse_values(input: &str) -> (i64, Vec<(i64, i64)>) {
+use super::schedule::Schedule;
+
+fn parse_values(input: &str) -> (i64, Schedule) {
     let mut lines = input.lines();
     let timestamp = lines.next().unwrap().parse().unwrap();
-    let mut busses = Vec::new();
-
-    for (offset, elements) in lines.next().unwrap().split(",").enumerate() {
-        if let Ok(bus_id) = elements.parse::<i64>() {
-            busses.push((offset as i64, bus_id));
-        }
-    }
+    let schedule = Schedule::parse(lines.next().unwrap());
 
-    (timestamp, busses)
+    (timestamp, schedule)
 }
 
 pub fn part1(input: &str) -> i64 {
-    let (timestamp, busses) = parse_values(input);
-    let (index, best) = busses
-        .into_iter()
+    let (timestamp, schedule) = parse_values(input);
+    let (index, best) = schedule
+        .busses()
         // timestamp % bus = how much time has passed since bus last departed
         // for example, timestamp = 8, bus = 7 => 8 % 7 = 1; bus departed t = 1 ago
         // bus - (timestamp % bus) = how much time to wait until bus departs
@@ -27,44 +23,11 @@ pub fn part1(input: &str) -> i64 {
     index * best
 }
 
-// https://rosettacode.org/wiki/Chinese_remainder_theorem#Rust
-fn egcd(a: i64, b: i64) -> (i64, i64, i64) {
-    if a == 0 {
-        (b, 0, 1)
-    } else {
-        let (g, x, y) = egcd(b % a, a);
-        (g, y - (b / a) * x, x)
-    }
-}
-
-fn mod_inv(x: i64, n: i64) -> Option<i64> {
-    let (g, x, _) = egcd(x, n);
-    if g == 1 {
-        Some((x % n + n) % n)
-    } else {
-        None
-    }
-}
-
-fn chinese_remainder(residues: &[i64], modulii: &[i64]) -> Option<i64> {
-    let prod = modulii.iter().product::<i64>();
-
-    let mut sum = 0;
-
-    for (&residue, &modulus) in residues.iter().zip(modulii) {
-        let p = prod / modulus;
-        sum += residue * mod_inv(p, modulus)? * p
-    }
-
-    Some(sum % prod)
-}
-
 pub fn part2(input: &str) -> i64 {
-    let (_, busses) = parse_values(input);
-
-    let (offsets, ids): (Vec<i64>, Vec<i64>) = busses.iter().cloned().unzip();
+    let (_, schedule) = parse_values(input);
 
-    ids.iter().product::<i64>() - chinese_remainder(&offsets, &ids).unwrap()
+    let everything: Vec<usize> = (0..schedule.slots.len()).collect();
+    schedule.aligns(0, &everything).unwrap()
 }
 
 #[cfg(test)]
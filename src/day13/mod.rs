@@ -1,4 +1,5 @@
 mod day13;
+mod schedule;
 
 pub use day13::part1;
 pub use day13::part2;
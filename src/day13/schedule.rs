@@ -0,0 +1,130 @@
+use aoc2020::number_theory::{chinese_remainder, lcm};
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Bus(i64),
+    Any, // an x, no constraint on this offset
+}
+
+// the bus list, where a slot's index is the offset its bus should depart at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub slots: Vec<Slot>,
+}
+
+impl Schedule {
+    pub fn parse(line: &str) -> Self {
+        let slots = line
+            .trim()
+            .split(',')
+            .map(|slot| match slot {
+                "x" => Slot::Any,
+                _ => Slot::Bus(slot.parse().unwrap()),
+            })
+            .collect();
+        Schedule { slots }
+    }
+
+    // (offset, bus_id) for every slot that isn't a wildcard
+    pub fn busses(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, slot)| match slot {
+                Slot::Bus(id) => Some((offset as i64, *id)),
+                Slot::Any => None,
+            })
+    }
+
+    // the first `n` departures of every bus at or after `timestamp`
+    pub fn departures(&self, timestamp: i64, n: usize) -> Vec<(i64, Vec<i64>)> {
+        self.busses()
+            .map(|(_, bus)| {
+                let first = (timestamp + bus - 1).div_euclid(bus) * bus;
+                let times = (0..n as i64).map(|i| first + i * bus).collect();
+                (bus, times)
+            })
+            .collect()
+    }
+
+    // first t at or after `timestamp` where the bus of every slot in `subset`
+    // departs at t + slot index. None if they never line up, if an offset is past
+    // the last slot or if t doesn't fit in an i64
+    pub fn aligns(&self, timestamp: i64, subset: &[usize]) -> Option<i64> {
+        let mut congruences: Vec<(i128, i128)> = Vec::new();
+        for &offset in subset {
+            if let Slot::Bus(id) = self.slots.get(offset)? {
+                congruences.push((-(offset as i128), *id as i128));
+            }
+        }
+        let (first, period) = chinese_remainder(&congruences).ok()?;
+
+        // first is the earliest alignment from 0, skip whole periods to reach timestamp
+        let timestamp = timestamp as i128;
+        let skipped = (timestamp - first + period - 1).div_euclid(period).max(0);
+        i64::try_from(first + skipped * period).ok()
+    }
+
+    // time after which every bus departs at the same offsets again, None if that
+    // doesn't fit in an i64
+    pub fn period(&self) -> Option<i64> {
+        self.busses().try_fold(1, |period, (_, bus)| {
+            i64::try_from(lcm(period as i128, bus as i128)?).ok()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Schedule, Slot};
+
+    fn example() -> Schedule {
+        let input = include_str!("example1");
+        Schedule::parse(input.lines().nth(1).unwrap())
+    }
+
+    #[test]
+    fn wildcards() {
+        let schedule = example();
+        assert_eq!(schedule.slots[2], Slot::Any);
+        assert_eq!(
+            schedule.busses().collect::<Vec<_>>(),
+            vec![(0, 7), (1, 13), (4, 59), (6, 31), (7, 19)]
+        );
+    }
+
+    #[test]
+    fn departures() {
+        let departures = example().departures(939, 2);
+        assert_eq!(departures[0], (7, vec![945, 952]));
+        assert_eq!(departures[2], (59, vec![944, 1003]));
+        assert_eq!(example().departures(945, 1)[0], (7, vec![945]));
+    }
+
+    #[test]
+    fn aligns() {
+        let schedule = example();
+        assert_eq!(schedule.aligns(0, &[0, 1, 2, 3, 4, 5, 6, 7]), Some(1068781));
+        // 7 at t, 13 at t + 1
+        assert_eq!(schedule.aligns(0, &[0, 1]), Some(77));
+        assert_eq!(schedule.aligns(78, &[0, 1]), Some(77 + 91));
+        assert_eq!(schedule.aligns(78, &[2, 3]), Some(78));
+
+        // 4 at t needs an even t, 6 at t + 1 an odd one
+        let conflict = Schedule::parse("4,6");
+        assert_eq!(conflict.aligns(0, &[0, 1]), None);
+
+        assert_eq!(schedule.aligns(0, &[0, 8]), None);
+        let huge = Schedule::parse("4294967311,4294967357");
+        assert_eq!(huge.aligns(0, &[0, 1]), Some(6817275104488154495));
+        assert_eq!(huge.aligns(6817275104488154496, &[0, 1]), None);
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(example().period(), Some(7 * 13 * 59 * 31 * 19));
+        assert_eq!(Schedule::parse("4,x,6").period(), Some(12));
+        assert_eq!(Schedule::parse("4294967311,4294967357").period(), None);
+    }
+}
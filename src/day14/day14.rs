@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-enum Instruction {
+use super::docking::{AddressDecoder, Docking, ValueDecoder};
+
+pub enum Instruction {
     Mask(u64, u64),   // Mask(xs, values)
     Update(u64, u64), // Update(location, value)
 }
@@ -27,7 +29,7 @@ fn read_mask(mask_str: &str) -> (u64, u64) {
     (xs, values)
 }
 
-fn read_input(input: &str) -> Vec<Instruction> {
+pub fn read_input(input: &str) -> Vec<Instruction> {
     let mut instructions = Vec::new();
     for line in input.lines() {
         if line.starts_with("mask") {
@@ -48,26 +50,16 @@ fn read_input(input: &str) -> Vec<Instruction> {
     instructions
 }
 
+const WORD_WIDTH: u32 = 36;
+
 pub fn part1(input: &str) -> u64 {
-    let instructions = read_input(input);
-    let mut xs = 0;
-    let mut values = 0;
-    let mut memory = HashMap::new();
-    for instruction in instructions {
-        match instruction {
-            Instruction::Mask(x, v) => {
-                xs = x;
-                values = v;
-            }
-            Instruction::Update(location, value) => {
-                let e = memory.entry(location).or_default();
-                *e = (value & xs) | values;
-            }
-        }
-    }
-    memory.values().sum()
+    let mut docking = Docking::new(ValueDecoder, WORD_WIDTH);
+    docking.run(&read_input(input));
+    docking.sum().unwrap()
 }
 
+// expands every combination of floating bits, kept as a reference for the
+// address sets used in part2
 fn get_all_floating(mut location: u64, mut xs: u64) -> Vec<u64> {
     location = location & !xs; // set every x to 0 first
     let mut floating_bits = Vec::new();
@@ -93,7 +85,7 @@ fn get_all_floating(mut location: u64, mut xs: u64) -> Vec<u64> {
     result
 }
 
-pub fn part2(input: &str) -> u64 {
+fn part2_expanded(input: &str) -> u64 {
     let instructions = read_input(input);
     let mut xs = 0;
     let mut values = 0;
@@ -116,6 +108,12 @@ pub fn part2(input: &str) -> u64 {
     memory.values().sum()
 }
 
+pub fn part2(input: &str) -> u64 {
+    let mut docking = Docking::new(AddressDecoder, WORD_WIDTH);
+    docking.run(&read_input(input));
+    docking.sum().unwrap()
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -129,4 +127,10 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 3683236147222);
     }
+
+    #[test]
+    fn part2_expanded() {
+        let input = include_str!("input");
+        assert_eq!(super::part2_expanded(input), super::part2(input));
+    }
 }
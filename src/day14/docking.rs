@@ -0,0 +1,155 @@
+use super::day14::Instruction;
+use super::floating::{AddressSet, FloatingMemory};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mask {
+    pub floating: u64, // X bits
+    pub ones: u64,     // 1 bits, everything else is a 0
+}
+
+// turns a mem[address] = value instruction into the writes it makes
+pub trait Decoder {
+    fn decode(&self, mask: &Mask, address: u64, value: u64) -> Vec<(AddressSet, u64)>;
+}
+
+// version 1, the mask applies to the value
+pub struct ValueDecoder;
+
+impl Decoder for ValueDecoder {
+    fn decode(&self, mask: &Mask, address: u64, value: u64) -> Vec<(AddressSet, u64)> {
+        vec![(
+            AddressSet::new(0, address),
+            (value & mask.floating) | mask.ones,
+        )]
+    }
+}
+
+// version 2, the mask applies to the address, and floating bits take every value
+pub struct AddressDecoder;
+
+impl Decoder for AddressDecoder {
+    fn decode(&self, mask: &Mask, address: u64, value: u64) -> Vec<(AddressSet, u64)> {
+        vec![(AddressSet::new(mask.floating, address | mask.ones), value)]
+    }
+}
+
+// the ferry's docking program, masks, addresses and values are `width` bits wide
+pub struct Docking<D: Decoder> {
+    decoder: D,
+    word: u64,
+    mask: Mask,
+    memory: FloatingMemory,
+}
+
+impl<D: Decoder> Docking<D> {
+    pub fn new(decoder: D, width: u32) -> Self {
+        assert!(width > 0 && width <= 64, "word width must be in 1..=64");
+        Docking {
+            decoder,
+            word: u64::MAX >> (64 - width),
+            mask: Mask::default(),
+            memory: FloatingMemory::new(),
+        }
+    }
+
+    pub fn execute(&mut self, instruction: &Instruction) {
+        match *instruction {
+            Instruction::Mask(floating, ones) => {
+                self.mask = Mask {
+                    floating: floating & self.word,
+                    ones: ones & self.word,
+                };
+            }
+            Instruction::Update(address, value) => {
+                let writes =
+                    self.decoder
+                        .decode(&self.mask, address & self.word, value & self.word);
+                for (addresses, value) in writes {
+                    self.memory.write(addresses, value & self.word);
+                }
+            }
+        }
+    }
+
+    pub fn run(&mut self, instructions: &[Instruction]) {
+        for instruction in instructions {
+            self.execute(instruction);
+        }
+    }
+
+    pub fn mask(&self) -> Mask {
+        self.mask
+    }
+
+    pub fn get(&self, address: u64) -> Option<u64> {
+        self.memory.get(address)
+    }
+
+    pub fn sum(&self) -> Option<u64> {
+        self.memory.sum()
+    }
+
+    // written memory as disjoint address sets
+    pub fn regions(&self) -> &[(AddressSet, u64)] {
+        self.memory.writes()
+    }
+
+    // every written address and its value, sorted by address
+    pub fn dump(&self) -> Vec<(u64, u64)> {
+        let mut result: Vec<(u64, u64)> = self
+            .regions()
+            .iter()
+            .flat_map(|(set, value)| set.addresses().into_iter().map(move |a| (a, *value)))
+            .collect();
+        result.sort_unstable();
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AddressDecoder, AddressSet, Decoder, Docking, Mask, ValueDecoder};
+    use crate::day14::day14::read_input;
+
+    #[test]
+    fn example_v1() {
+        let input =
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 11\nmem[7] = 101\nmem[8] = 0";
+        let mut docking = Docking::new(ValueDecoder, 36);
+        docking.run(&read_input(input));
+        assert_eq!(docking.dump(), vec![(7, 101), (8, 64)]);
+        assert_eq!(docking.sum(), Some(165));
+    }
+
+    #[test]
+    fn example_v2() {
+        let input = "mask = 000000000000000000000000000000X1001X\nmem[42] = 100\nmask = 00000000000000000000000000000000X0XX\nmem[26] = 1";
+        let mut docking = Docking::new(AddressDecoder, 36);
+        docking.run(&read_input(input));
+        assert_eq!(docking.get(59), Some(100));
+        assert_eq!(docking.get(16), Some(1));
+        assert_eq!(docking.get(0), None);
+        assert_eq!(docking.sum(), Some(208));
+    }
+
+    // writes the value to the address and the one mirrored around the mask
+    struct MirrorDecoder;
+
+    impl Decoder for MirrorDecoder {
+        fn decode(&self, mask: &Mask, address: u64, value: u64) -> Vec<(AddressSet, u64)> {
+            vec![
+                (AddressSet::new(0, address), value),
+                (AddressSet::new(0, address ^ mask.ones), value),
+            ]
+        }
+    }
+
+    #[test]
+    fn custom_width() {
+        let input = "mask = 1111\nmem[1] = 255\nmem[20] = 3";
+        let mut docking = Docking::new(MirrorDecoder, 4);
+        docking.run(&read_input(input));
+        // values and addresses are cut to 4 bits
+        assert_eq!(docking.dump(), vec![(1, 15), (4, 3), (11, 3), (14, 15)]);
+    }
+}
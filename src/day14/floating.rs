@@ -0,0 +1,166 @@
+use std::convert::TryFrom;
+
+// every address that matches `fixed` on all the bits that aren't `floating`,
+// `fixed` is always 0 on floating bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressSet {
+    pub floating: u64,
+    pub fixed: u64,
+}
+
+impl AddressSet {
+    pub fn new(floating: u64, address: u64) -> Self {
+        AddressSet {
+            floating,
+            fixed: address & !floating,
+        }
+    }
+
+    // a u128 since all 64 bits can float
+    pub fn size(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        address & !self.floating == self.fixed
+    }
+
+    pub fn intersection(&self, other: &AddressSet) -> Option<AddressSet> {
+        // bits fixed in both sets have to agree
+        let both_fixed = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & both_fixed != 0 {
+            return None;
+        }
+        Some(AddressSet {
+            floating: self.floating & other.floating,
+            fixed: self.fixed | other.fixed,
+        })
+    }
+
+    // addresses in self but not in other, as disjoint sets
+    pub fn subtract(&self, other: &AddressSet) -> Vec<AddressSet> {
+        let common = match self.intersection(other) {
+            Some(common) => common,
+            None => return vec![*self],
+        };
+
+        // one floating bit at a time, the half where that bit differs from the
+        // intersection is outside of other, and we keep narrowing the other half
+        let mut result = Vec::new();
+        let mut remaining = *self;
+        let mut split = self.floating & !common.floating;
+        while split != 0 {
+            let bit = split & split.wrapping_neg();
+            split &= !bit;
+            result.push(AddressSet {
+                floating: remaining.floating & !bit,
+                fixed: remaining.fixed | (!common.fixed & bit),
+            });
+            remaining = AddressSet {
+                floating: remaining.floating & !bit,
+                fixed: remaining.fixed | (common.fixed & bit),
+            };
+        }
+        // remaining is now the intersection, which is entirely inside other
+        result
+    }
+
+    // every address in the set, 2^floating bits of them
+    pub fn addresses(&self) -> Vec<u64> {
+        let mut result = vec![self.fixed];
+        let mut floating = self.floating;
+        while floating != 0 {
+            let bit = floating & floating.wrapping_neg();
+            floating &= !bit;
+            for idx in 0..result.len() {
+                result.push(result[idx] | bit);
+            }
+        }
+        result
+    }
+}
+
+// memory as a list of disjoint address sets and the value stored in them, a later
+// write removes its addresses from every earlier set instead of expanding them
+#[derive(Debug, Default)]
+pub struct FloatingMemory {
+    writes: Vec<(AddressSet, u64)>,
+}
+
+impl FloatingMemory {
+    pub fn new() -> Self {
+        FloatingMemory { writes: Vec::new() }
+    }
+
+    pub fn write(&mut self, addresses: AddressSet, value: u64) {
+        let mut writes = Vec::with_capacity(self.writes.len() + 1);
+        for (set, old) in self.writes.drain(..) {
+            for remaining in set.subtract(&addresses) {
+                writes.push((remaining, old));
+            }
+        }
+        writes.push((addresses, value));
+        self.writes = writes;
+    }
+
+    pub fn writes(&self) -> &[(AddressSet, u64)] {
+        &self.writes
+    }
+
+    pub fn get(&self, address: u64) -> Option<u64> {
+        self.writes
+            .iter()
+            .find(|(set, _)| set.contains(address))
+            .map(|(_, value)| *value)
+    }
+
+    // None if the sum doesn't fit in a u64
+    pub fn sum(&self) -> Option<u64> {
+        let sum = self.writes.iter().try_fold(0u128, |sum, (set, value)| {
+            sum.checked_add(set.size() * *value as u128)
+        })?;
+        u64::try_from(sum).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AddressSet, FloatingMemory};
+
+    #[test]
+    fn subtract() {
+        // 0b0XX0 minus 0b0X10 = { 0b0000, 0b0100 }
+        let a = AddressSet::new(0b0110, 0);
+        let b = AddressSet::new(0b0100, 0b0010);
+        let mut rest: Vec<u64> = a.subtract(&b).iter().flat_map(|s| s.addresses()).collect();
+        rest.sort_unstable();
+        assert_eq!(rest, vec![0b0000, 0b0100]);
+
+        assert_eq!(a.subtract(&AddressSet::new(0, 0b1000)), vec![a]);
+        assert!(b.subtract(&a).is_empty());
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut memory = FloatingMemory::new();
+        memory.write(AddressSet::new(0b1111, 0), 1);
+        memory.write(AddressSet::new(0b0011, 0b0100), 10);
+        assert_eq!(memory.get(0b0101), Some(10));
+        assert_eq!(memory.get(0b1000), Some(1));
+        assert_eq!(memory.get(0b10000), None);
+        assert_eq!(memory.sum(), Some(12 + 4 * 10));
+    }
+
+    #[test]
+    fn every_bit_floating() {
+        let everything = AddressSet::new(u64::MAX, 0);
+        assert_eq!(everything.size(), 1 << 64);
+        let mut memory = FloatingMemory::new();
+        memory.write(everything, 0);
+        assert_eq!(memory.sum(), Some(0));
+        memory.write(AddressSet::new(0, 7), 5);
+        assert_eq!(memory.sum(), Some(5));
+        memory.write(everything, 1);
+        assert_eq!(memory.sum(), None);
+    }
+}
@@ -1,4 +1,6 @@
 mod day14;
+mod docking;
+mod floating;
 
 pub use day14::part1;
 pub use day14::part2;
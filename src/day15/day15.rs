@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use super::engine::MemoryGame;
 
 fn read_input(input: &str) -> Vec<u32> {
     let mut nums = Vec::new();
@@ -8,29 +8,8 @@ fn read_input(input: &str) -> Vec<u32> {
     nums
 }
 
-fn memory_game(mut nums: Vec<u32>, nth: u32) -> u32 {
-    let mut seen: HashMap<u32, u32> = HashMap::new();
-
-    let turn = nums.len() as u32 + 1;
-    // we dont want the last number in the seen map yet as we only add the number
-    // from previous turn on the turn after it. This allows us to only keep a single
-    // number inside the HashMap instead of the last two indexes. Whenever we have to
-    // look up an index, we know last one is for sure on turn -1, and are only required
-    // to look up the other index.
-    let mut last = nums.pop().unwrap();
-    // starting numbers
-    for (idx, &num) in nums.iter().enumerate() {
-        seen.insert(num, idx as u32 + 1); // turns start at 1
-    }
-
-    for turn in turn..=nth {
-        let seen_at_option = seen.insert(last, turn - 1);
-        last = match seen_at_option {
-            Some(val) => turn - 1 - val,
-            None => 0,
-        };
-    }
-    last
+fn memory_game(nums: Vec<u32>, nth: u32) -> u32 {
+    MemoryGame::new(nums).nth(nth as usize - 1).unwrap()
 }
 
 pub fn part1(input: &str) -> u32 {
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+// numbers below this are stored in a flat vector, bigger ones are rare and go into a map
+const DENSE_LIMIT: u32 = 1 << 22;
+
+// turns when a number was spoken, 0 means never (turns start at 1)
+#[derive(Debug, Clone, Copy, Default)]
+struct Record {
+    first: u32,
+    last: u32,
+}
+
+// The memory game as an iterator over the spoken numbers, starting numbers included.
+// Small numbers are spoken over and over, so their records live in a vector indexed
+// by the number. Numbers can get as big as the amount of turns played, but big
+// numbers are spoken rarely and mostly once, so a map keeps them without allocating
+// a record for every number in between.
+pub struct MemoryGame {
+    starting: Vec<u32>,
+    turn: u32,
+    next: u32,
+    dense_limit: u32,
+    dense: Vec<Record>,
+    sparse: HashMap<u32, Record>,
+}
+
+impl MemoryGame {
+    pub fn new(starting: Vec<u32>) -> Self {
+        MemoryGame::with_dense_limit(starting, DENSE_LIMIT)
+    }
+
+    pub fn with_dense_limit(starting: Vec<u32>, dense_limit: u32) -> Self {
+        MemoryGame {
+            starting,
+            turn: 0,
+            next: 0,
+            dense_limit,
+            dense: Vec::new(),
+            sparse: HashMap::new(),
+        }
+    }
+
+    fn record_mut(&mut self, num: u32) -> &mut Record {
+        if num < self.dense_limit {
+            let idx = num as usize;
+            if idx >= self.dense.len() {
+                let len = (idx + 1).max(self.dense.len() * 2);
+                self.dense
+                    .resize(len.min(self.dense_limit as usize), Record::default());
+            }
+            &mut self.dense[idx]
+        } else {
+            self.sparse.entry(num).or_default()
+        }
+    }
+
+    fn record(&self, num: u32) -> Record {
+        if num < self.dense_limit {
+            self.dense.get(num as usize).copied().unwrap_or_default()
+        } else {
+            self.sparse.get(&num).copied().unwrap_or_default()
+        }
+    }
+
+    // turns played so far
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    pub fn first_spoken(&self, num: u32) -> Option<u32> {
+        match self.record(num).first {
+            0 => None,
+            turn => Some(turn),
+        }
+    }
+
+    pub fn last_spoken(&self, num: u32) -> Option<u32> {
+        match self.record(num).last {
+            0 => None,
+            turn => Some(turn),
+        }
+    }
+
+    // how many different numbers have been spoken
+    pub fn distinct(&self) -> usize {
+        self.dense.iter().filter(|record| record.first != 0).count() + self.sparse.len()
+    }
+}
+
+impl Iterator for MemoryGame {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let num = match self.starting.get(self.turn as usize) {
+            Some(&num) => num,
+            None => self.next,
+        };
+        self.turn += 1;
+        let turn = self.turn;
+
+        let record = self.record_mut(num);
+        let previous = record.last;
+        record.last = turn;
+        if record.first == 0 {
+            record.first = turn;
+        }
+        // what gets spoken on the turn after this one
+        self.next = if previous == 0 { 0 } else { turn - previous };
+
+        Some(num)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryGame;
+
+    #[test]
+    fn sequence() {
+        let game = MemoryGame::new(vec![0, 3, 6]);
+        let spoken: Vec<u32> = game.take(10).collect();
+        assert_eq!(spoken, vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]);
+    }
+
+    #[test]
+    fn queries() {
+        let mut game = MemoryGame::new(vec![0, 3, 6]);
+        game.by_ref().take(10).for_each(drop);
+        assert_eq!(game.turn(), 10);
+        assert_eq!(game.first_spoken(3), Some(2));
+        assert_eq!(game.last_spoken(3), Some(6));
+        assert_eq!(game.last_spoken(0), Some(10));
+        assert_eq!(game.first_spoken(2), None);
+        assert_eq!(game.distinct(), 5);
+    }
+
+    #[test]
+    fn sparse() {
+        // a tiny dense limit puts nearly everything in the map, the sequence is the same
+        let dense: Vec<u32> = MemoryGame::new(vec![1, 3, 2]).take(2020).collect();
+        let sparse: Vec<u32> = MemoryGame::with_dense_limit(vec![1, 3, 2], 4)
+            .take(2020)
+            .collect();
+        assert_eq!(dense, sparse);
+        assert_eq!(dense[2019], 1);
+    }
+}
@@ -1,4 +1,5 @@
 mod day15;
+mod engine;
 
 pub use day15::part1;
 pub use day15::part2;
@@ -1,3 +1,4 @@
+use aoc2020::matching::matching;
 use std::{
     collections::{HashMap, HashSet},
     ops::RangeInclusive,
@@ -117,19 +118,7 @@ pub fn part2(input: &str) -> u64 {
         map.insert(rule.name.clone(), valid_options);
     }
 
-    let mut name_index = HashMap::new();
-    while map.len() > 0 {
-        let definite: Vec<_> = map.drain_filter(|_, v| v.len() == 1).collect();
-        for (k, v) in definite {
-            let finished = v.into_iter().next().unwrap();
-            name_index.insert(k, finished);
-            for (_, indexes) in map.iter_mut() {
-                if indexes.contains(&finished) {
-                    indexes.remove(&finished);
-                }
-            }
-        }
-    }
+    let name_index = matching(map).unwrap();
 
     name_index
         .iter()
@@ -1,5 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
+use aoc2020::image::{self, Bounds, Frames, BLACK, WHITE};
+use aoc2020::Vector2;
+
 type Universe = HashSet<(i32, i32, i32, i32)>;
 
 fn parse_plane(input: &str) -> Universe {
@@ -119,6 +122,37 @@ pub fn part2(input: &str) -> usize {
     universe.len()
 }
 
+// the z = 0 layer of every part 1 cycle, all frames the size of the biggest one
+pub fn frames(input: &str, cycles: usize) -> Frames {
+    let mut universe = parse_plane(input);
+    let mut layers = Vec::new();
+    for cycle in 0..=cycles {
+        if cycle > 0 {
+            universe = step3d(&universe);
+        }
+        let layer: Vec<Vector2> = universe
+            .iter()
+            .filter(|&&(_, _, z, _)| z == 0)
+            .map(|&(x, y, _, _)| Vector2::new(x as i64, y as i64))
+            .collect();
+        layers.push(layer);
+    }
+
+    let bounds = layers
+        .iter()
+        .filter_map(|layer| Bounds::of(layer.iter()))
+        .fold(None, |all: Option<Bounds>, bounds| {
+            Some(all.map_or(bounds, |all| all.merge(&bounds)))
+        });
+    let mut frames = Frames::new();
+    if let Some(bounds) = bounds {
+        for layer in layers.iter() {
+            frames.push(image::from_points(layer.iter(), bounds, BLACK, WHITE));
+        }
+    }
+    frames
+}
+
 mod test {
     #[test]
     fn part1() {
@@ -131,4 +165,22 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 2532);
     }
+
+    #[test]
+    fn frames() {
+        let frames = super::frames(".#.\n..#\n###", 3);
+        assert_eq!(frames.images.len(), 4);
+        let first = &frames.images[0];
+        assert!(first.width >= 3 && first.height >= 3);
+        assert!(frames
+            .images
+            .iter()
+            .all(|image| (image.width, image.height) == (first.width, first.height)));
+        let black = first
+            .pixels
+            .iter()
+            .filter(|&&p| p == aoc2020::image::BLACK)
+            .count();
+        assert_eq!(black, 5);
+    }
 }
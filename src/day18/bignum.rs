@@ -0,0 +1,133 @@
+use std::fmt;
+
+// each limb holds 9 decimal digits, so printing is just printing every limb
+// zero padded, and a limb product (< 10^18) still fits in a u64
+const BASE: u64 = 1_000_000_000;
+
+// arbitrary precision unsigned integer, limbs stored least significant first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+        for i in 0..len {
+            let a = *self.limbs.get(i).unwrap_or(&0) as u64;
+            let b = *other.limbs.get(i).unwrap_or(&0) as u64;
+            let sum = a + b + carry;
+            limbs.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry as u32);
+        }
+        BigUint { limbs }
+    }
+
+    pub fn mul(&self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+        let mut acc = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let cur = acc[i + j] + a as u64 * b as u64 + carry;
+                acc[i + j] = cur % BASE;
+                carry = cur / BASE;
+            }
+            acc[i + other.limbs.len()] += carry;
+        }
+        let mut limbs: Vec<u32> = acc.into_iter().map(|limb| limb as u32).collect();
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigUint { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(mut value: u64) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push((value % BASE) as u32);
+            value /= BASE;
+        }
+        BigUint { limbs }
+    }
+}
+
+impl BigUint {
+    // ascii decimal digits only, of any length
+    pub fn from_decimal(digits: &str) -> Self {
+        let digits = digits.trim_start_matches('0').as_bytes();
+        let mut limbs = Vec::with_capacity(digits.len() / 9 + 1);
+        for chunk in digits.rchunks(9) {
+            let limb = chunk.iter().fold(0, |limb, &digit| {
+                assert!(digit.is_ascii_digit(), "not a decimal digit");
+                limb * 10 + (digit - b'0') as u32
+            });
+            limbs.push(limb);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut it = self.limbs.iter().rev();
+        match it.next() {
+            Some(most_significant) => write!(f, "{}", most_significant)?,
+            None => return write!(f, "0"),
+        }
+        for limb in it {
+            write!(f, "{:09}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BigUint;
+
+    #[test]
+    fn display() {
+        assert_eq!(BigUint::zero().to_string(), "0");
+        assert_eq!(BigUint::from(1_000_000_007).to_string(), "1000000007");
+        assert_eq!(BigUint::from(u64::MAX).to_string(), u64::MAX.to_string());
+        assert_eq!(BigUint::from_decimal("000"), BigUint::zero());
+        let wide = "123456789012345678901234567890";
+        assert_eq!(BigUint::from_decimal(wide).to_string(), wide);
+        assert_eq!(
+            BigUint::from_decimal("1000000000"),
+            BigUint::from(1_000_000_000)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let max = BigUint::from(u64::MAX);
+        let square = max.mul(&max);
+        assert_eq!(
+            square.to_string(),
+            (u64::MAX as u128 * u64::MAX as u128).to_string()
+        );
+        let sum = square.add(&max);
+        let expected = u64::MAX as u128 * u64::MAX as u128 + u64::MAX as u128;
+        assert_eq!(sum.to_string(), expected.to_string());
+        assert_eq!(max.mul(&BigUint::zero()), BigUint::zero());
+    }
+}
@@ -1,10 +1,15 @@
+use std::convert::Infallible;
+
+use super::bignum::BigUint;
+
 #[derive(Debug)]
 enum Token {
     Mul,
     Add,
     OpenParenthesis,
     CloseParenthesis,
-    Num(i64),
+    // kept as digits, each backend decides what fits
+    Num(String),
 }
 
 fn find_closing_parenthesis(tokens: &[Token]) -> usize {
@@ -34,8 +39,12 @@ fn parse_token(token: &str) -> Token {
         "*" => Token::Mul,
         ")" => Token::CloseParenthesis,
         _ => {
-            let num = token.parse::<i64>().unwrap();
-            Token::Num(num)
+            assert!(
+                token.bytes().all(|b| b.is_ascii_digit()),
+                "not a number: {}",
+                token
+            );
+            Token::Num(token.to_string())
         }
     }
 }
@@ -58,20 +67,64 @@ fn parse_input(input: &str) -> Vec<Vec<Token>> {
     res
 }
 
-fn evaluate(mut tokens: &[Token]) -> i64 {
-    let (mut left_val, idx) = consume(tokens);
+// numeric backend for evaluation, Error is what stops it when a literal or a
+// result can't be represented
+trait Value: Sized {
+    type Error;
+    fn zero() -> Self;
+    fn literal(digits: &str) -> Result<Self, Self::Error>;
+    fn checked_add(self, other: Self) -> Result<Self, Self::Error>;
+    fn checked_mul(self, other: Self) -> Result<Self, Self::Error>;
+}
+
+impl Value for i64 {
+    type Error = ();
+    fn zero() -> Self {
+        0
+    }
+    fn literal(digits: &str) -> Result<Self, ()> {
+        // the tokens are all digits, so this only fails when it's too big
+        digits.parse().map_err(|_| ())
+    }
+    fn checked_add(self, other: Self) -> Result<Self, ()> {
+        i64::checked_add(self, other).ok_or(())
+    }
+    fn checked_mul(self, other: Self) -> Result<Self, ()> {
+        i64::checked_mul(self, other).ok_or(())
+    }
+}
+
+// the puzzle only has non negative numbers and no subtraction
+impl Value for BigUint {
+    type Error = Infallible;
+    fn zero() -> Self {
+        BigUint::zero()
+    }
+    fn literal(digits: &str) -> Result<Self, Infallible> {
+        Ok(BigUint::from_decimal(digits))
+    }
+    fn checked_add(self, other: Self) -> Result<Self, Infallible> {
+        Ok(self.add(&other))
+    }
+    fn checked_mul(self, other: Self) -> Result<Self, Infallible> {
+        Ok(self.mul(&other))
+    }
+}
+
+fn evaluate<T: Value>(mut tokens: &[Token]) -> Result<T, T::Error> {
+    let (mut left_val, idx) = consume::<T>(tokens)?;
     tokens = &tokens[idx..];
-    while tokens.len() > 0 {
+    while !tokens.is_empty() {
         let idx = match tokens[0] {
             Token::Mul => {
                 // consume tokens as soon as we can
-                let (right_val, idx) = consume(&tokens[1..]);
-                left_val *= right_val;
+                let (right_val, idx) = consume(&tokens[1..])?;
+                left_val = left_val.checked_mul(right_val)?;
                 idx + 1
             }
             Token::Add => {
-                let (right_val, idx) = consume(&tokens[1..]);
-                left_val += right_val;
+                let (right_val, idx) = consume(&tokens[1..])?;
+                left_val = left_val.checked_add(right_val)?;
                 idx + 1
             }
             _ => {
@@ -80,12 +133,12 @@ fn evaluate(mut tokens: &[Token]) -> i64 {
         };
         tokens = &tokens[idx..];
     }
-    left_val
+    Ok(left_val)
 }
 
 // returns (expression_value, tokens_read)
-fn consume(tokens: &[Token]) -> (i64, usize) {
-    match tokens[0] {
+fn consume<T: Value>(tokens: &[Token]) -> Result<(T, usize), T::Error> {
+    match &tokens[0] {
         Token::Mul => {
             unreachable!()
         }
@@ -96,33 +149,33 @@ fn consume(tokens: &[Token]) -> (i64, usize) {
             // evaluate the inside of a parenthesis expression
             // and return (value, tokens_read)
             let close_index = find_closing_parenthesis(tokens);
-            let val = evaluate(&tokens[1..close_index]);
-            (val, close_index + 1)
+            let val = evaluate(&tokens[1..close_index])?;
+            Ok((val, close_index + 1))
         }
         Token::CloseParenthesis => {
             unreachable!()
         }
-        Token::Num(val) => (val, 1),
+        Token::Num(digits) => Ok((T::literal(digits)?, 1)),
     }
 }
 
 // exact same code as above, except it evaluates right on mul
 // instead of consuming the value
-fn evaluate_priority(mut tokens: &[Token]) -> i64 {
-    let (mut left_val, idx) = consume_priority(tokens);
+fn evaluate_priority<T: Value>(mut tokens: &[Token]) -> Result<T, T::Error> {
+    let (mut left_val, idx) = consume_priority::<T>(tokens)?;
     tokens = &tokens[idx..];
-    while tokens.len() > 0 {
+    while !tokens.is_empty() {
         let idx = match tokens[0] {
             Token::Mul => {
                 // evaluate rest of tokens and multiply after
                 // afterwards, the entire token stream is done
-                let right_val = evaluate_priority(&tokens[1..]);
-                left_val *= right_val;
+                let right_val = evaluate_priority(&tokens[1..])?;
+                left_val = left_val.checked_mul(right_val)?;
                 tokens.len()
             }
             Token::Add => {
-                let (right_val, idx) = consume_priority(&tokens[1..]);
-                left_val += right_val;
+                let (right_val, idx) = consume_priority(&tokens[1..])?;
+                left_val = left_val.checked_add(right_val)?;
                 idx + 1
             }
             _ => {
@@ -131,11 +184,11 @@ fn evaluate_priority(mut tokens: &[Token]) -> i64 {
         };
         tokens = &tokens[idx..];
     }
-    left_val
+    Ok(left_val)
 }
 
-fn consume_priority(tokens: &[Token]) -> (i64, usize) {
-    match tokens[0] {
+fn consume_priority<T: Value>(tokens: &[Token]) -> Result<(T, usize), T::Error> {
+    match &tokens[0] {
         Token::Mul => {
             unreachable!()
         }
@@ -144,32 +197,71 @@ fn consume_priority(tokens: &[Token]) -> (i64, usize) {
         }
         Token::OpenParenthesis => {
             let close_index = find_closing_parenthesis(tokens);
-            let val = evaluate_priority(&tokens[1..close_index]);
-            (val, close_index + 1)
+            let val = evaluate_priority(&tokens[1..close_index])?;
+            Ok((val, close_index + 1))
         }
         Token::CloseParenthesis => {
             unreachable!()
         }
-        Token::Num(val) => (val, 1),
+        Token::Num(digits) => Ok((T::literal(digits)?, 1)),
     }
 }
 
-pub fn part1(input: &str) -> i64 {
+// line (1-based) with a number or a value, or the running total after adding
+// it, that didn't fit
+#[derive(Debug, PartialEq)]
+pub struct Overflow {
+    pub line: usize,
+}
+
+// the error comes with the line it happened on
+fn sum_lines<T: Value>(
+    input: &str,
+    evaluator: fn(&[Token]) -> Result<T, T::Error>,
+) -> Result<T, (usize, T::Error)> {
     let programs = parse_input(input);
-    let mut res = 0;
-    for tokens in programs {
-        res += evaluate(&tokens);
+    let mut res = T::zero();
+    for (idx, tokens) in programs.iter().enumerate() {
+        res = evaluator(tokens)
+            .and_then(|val| res.checked_add(val))
+            .map_err(|error| (idx + 1, error))?;
     }
-    res
+    Ok(res)
 }
 
-pub fn part2(input: &str) -> i64 {
-    let programs = parse_input(input);
-    let mut res = 0;
-    for tokens in programs {
-        res += evaluate_priority(&tokens);
+fn checked_sum(input: &str, evaluator: fn(&[Token]) -> Result<i64, ()>) -> Result<i64, Overflow> {
+    sum_lines(input, evaluator).map_err(|(line, _)| Overflow { line })
+}
+
+fn big_sum(input: &str, evaluator: fn(&[Token]) -> Result<BigUint, Infallible>) -> BigUint {
+    match sum_lines(input, evaluator) {
+        Ok(sum) => sum,
+        Err((_, never)) => match never {},
     }
-    res
+}
+
+pub fn checked_part1(input: &str) -> Result<i64, Overflow> {
+    checked_sum(input, evaluate)
+}
+
+pub fn checked_part2(input: &str) -> Result<i64, Overflow> {
+    checked_sum(input, evaluate_priority)
+}
+
+pub fn big_part1(input: &str) -> BigUint {
+    big_sum(input, evaluate)
+}
+
+pub fn big_part2(input: &str) -> BigUint {
+    big_sum(input, evaluate_priority)
+}
+
+pub fn part1(input: &str) -> i64 {
+    checked_part1(input).unwrap()
+}
+
+pub fn part2(input: &str) -> i64 {
+    checked_part2(input).unwrap()
 }
 
 mod test {
@@ -184,4 +276,40 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 158183007916215);
     }
+
+    #[test]
+    fn big_matches_checked() {
+        let input = include_str!("input");
+        assert_eq!(super::big_part1(input).to_string(), "24650385570008");
+        assert_eq!(super::big_part2(input).to_string(), "158183007916215");
+    }
+
+    #[test]
+    fn overflow() {
+        let input = "1 + 2\n(99999 * 99999) * 99999 * 99999 * 99999\n3";
+        assert_eq!(
+            super::checked_part1(input),
+            Err(super::Overflow { line: 2 })
+        );
+        assert_eq!(
+            super::big_part1(input).to_string(),
+            "9999500009999900000500005"
+        );
+    }
+
+    #[test]
+    fn wide_literal() {
+        // 2^64 doesn't even parse as an i64
+        let input = "1 + 2\n18446744073709551616 * (2 + 3)";
+        assert_eq!(
+            super::checked_part1(input),
+            Err(super::Overflow { line: 2 })
+        );
+        assert_eq!(
+            super::checked_part2(input),
+            Err(super::Overflow { line: 2 })
+        );
+        assert_eq!(super::big_part1(input).to_string(), "92233720368547758083");
+        assert_eq!(super::big_part2(input).to_string(), "92233720368547758083");
+    }
 }
@@ -1,3 +1,4 @@
+mod bignum;
 mod day18;
 
 pub use day18::part1;
@@ -0,0 +1,488 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// a set of characters like [a-z_] or [^0-9]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    pub ranges: Vec<(char, char)>,
+    pub negated: bool,
+}
+
+impl CharClass {
+    pub fn contains(&self, c: char) -> bool {
+        let found = self.ranges.iter().any(|&(low, high)| low <= c && c <= high);
+        found != self.negated
+    }
+}
+
+// written back as [a-z_], escaping the characters that are special inside a class
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let escape = |c: char| {
+            if "\\[]^-".contains(c) {
+                format!("\\{}", c)
+            } else {
+                c.to_string()
+            }
+        };
+        write!(f, "[")?;
+        if self.negated {
+            write!(f, "^")?;
+        }
+        for &(low, high) in &self.ranges {
+            if low == high {
+                write!(f, "{}", escape(low))?;
+            } else {
+                write!(f, "{}-{}", escape(low), escape(high))?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    Literal(String), // an empty literal matches the empty string
+    Class(CharClass),
+    Rule(String),
+}
+
+// an alternative with no terms is an epsilon production
+pub type Alternative = Vec<Term>;
+
+// Rules are named instead of numbered, and written as
+//   name: term term | term
+// where a term is either a "literal", a [class] or another rule name.
+// An empty alternative, or "", matches the empty string.
+// day19 inputs are valid grammars, with rules named "0", "1", ...
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    pub rules: BTreeMap<String, Vec<Alternative>>,
+    pub start: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Diagnostic {
+    UndefinedRule { rule: String, reference: String },
+    LeftRecursion(String),
+    Unreachable(String),
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::UndefinedRule { rule, reference } => {
+                write!(f, "rule {} references undefined rule {}", rule, reference)
+            }
+            Diagnostic::LeftRecursion(rule) => write!(f, "rule {} is left recursive", rule),
+            Diagnostic::Unreachable(rule) => write!(f, "rule {} is unreachable", rule),
+        }
+    }
+}
+
+// reads a class up to its closing ']', the way CharClass is displayed: a leading
+// '^' negates it and '\' makes the next character plain
+fn parse_class(it: &mut impl Iterator<Item = char>) -> Result<CharClass, &'static str> {
+    let unterminated = "unterminated character class";
+    // every character, and whether it was escaped
+    let mut chars = Vec::new();
+    loop {
+        match it.next().ok_or(unterminated)? {
+            ']' => break,
+            '\\' => chars.push((it.next().ok_or(unterminated)?, true)),
+            c => chars.push((c, false)),
+        }
+    }
+    let negated = chars.first() == Some(&('^', false));
+    let chars = if negated { &chars[1..] } else { &chars[..] };
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if i + 2 < chars.len() && chars[i + 1] == ('-', false) {
+            let (low, high) = (chars[i].0, chars[i + 2].0);
+            if low > high {
+                return Err("invalid character class");
+            }
+            ranges.push((low, high));
+            i += 3;
+        } else {
+            ranges.push((chars[i].0, chars[i].0));
+            i += 1;
+        }
+    }
+    Ok(CharClass { ranges, negated })
+}
+
+fn parse_alternatives(text: &str) -> Result<Vec<Alternative>, &'static str> {
+    let mut alternatives = vec![Vec::new()];
+    let mut it = text.chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            ' ' | '\t' => {}
+            '|' => alternatives.push(Vec::new()),
+            '"' => {
+                let mut literal = String::new();
+                loop {
+                    match it.next().ok_or("unterminated literal")? {
+                        '"' => break,
+                        c => literal.push(c),
+                    }
+                }
+                if !literal.is_empty() {
+                    alternatives
+                        .last_mut()
+                        .unwrap()
+                        .push(Term::Literal(literal));
+                }
+            }
+            '[' => {
+                let class = parse_class(&mut it)?;
+                alternatives.last_mut().unwrap().push(Term::Class(class));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(&c) = it.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    it.next();
+                }
+                alternatives.last_mut().unwrap().push(Term::Rule(name));
+            }
+            _ => return Err("unexpected character"),
+        }
+    }
+    Ok(alternatives)
+}
+
+impl Grammar {
+    pub fn from_text(text: &str, start: &str) -> Result<Self, SyntaxError> {
+        let mut rules = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let error = |reason| SyntaxError {
+                line: idx + 1,
+                reason,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (name, body) = line.split_once(':').ok_or_else(|| error("missing ':'"))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(error("missing rule name"));
+            }
+            let alternatives = parse_alternatives(body).map_err(error)?;
+            if rules.insert(name.to_string(), alternatives).is_some() {
+                return Err(error("rule defined twice"));
+            }
+        }
+        Ok(Grammar {
+            rules,
+            start: start.to_string(),
+        })
+    }
+
+    // rules that can match the empty string
+    pub fn nullable(&self) -> BTreeSet<&str> {
+        let mut nullable = BTreeSet::new();
+        loop {
+            let before = nullable.len();
+            for (name, alternatives) in &self.rules {
+                let empty = alternatives.iter().any(|alternative| {
+                    alternative.iter().all(|term| match term {
+                        Term::Literal(literal) => literal.is_empty(),
+                        Term::Class(_) => false,
+                        Term::Rule(inner) => nullable.contains(inner.as_str()),
+                    })
+                });
+                if empty {
+                    nullable.insert(name.as_str());
+                }
+            }
+            if nullable.len() == before {
+                return nullable;
+            }
+        }
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut result = Vec::new();
+
+        for (name, alternatives) in &self.rules {
+            for term in alternatives.iter().flatten() {
+                if let Term::Rule(reference) = term {
+                    if !self.rules.contains_key(reference) {
+                        result.push(Diagnostic::UndefinedRule {
+                            rule: name.clone(),
+                            reference: reference.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        if !self.rules.contains_key(&self.start) {
+            result.push(Diagnostic::UndefinedRule {
+                rule: self.start.clone(),
+                reference: self.start.clone(),
+            });
+        }
+
+        // rule a leads with rule b if b can be the first thing a expands to
+        let nullable = self.nullable();
+        let mut leads: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (name, alternatives) in &self.rules {
+            let entry = leads.entry(name.as_str()).or_default();
+            for alternative in alternatives {
+                for term in alternative {
+                    match term {
+                        Term::Rule(inner) => {
+                            entry.insert(inner.as_str());
+                            if !nullable.contains(inner.as_str()) {
+                                break;
+                            }
+                        }
+                        Term::Literal(literal) if literal.is_empty() => {}
+                        _ => break,
+                    }
+                }
+            }
+        }
+        for name in self.rules.keys() {
+            if self.reachable(&leads, name).contains(name.as_str()) {
+                result.push(Diagnostic::LeftRecursion(name.clone()));
+            }
+        }
+
+        let references = self.references();
+        let mut reachable = self.reachable(&references, &self.start);
+        reachable.insert(&self.start);
+        for name in self.rules.keys() {
+            if !reachable.contains(name.as_str()) {
+                result.push(Diagnostic::Unreachable(name.clone()));
+            }
+        }
+
+        result
+    }
+
+    // true if a rule used by start can expand back into itself, in which case the
+    // language may be infinite
+    pub fn is_recursive(&self) -> bool {
+        let references = self.references();
+        let mut used = self.reachable(&references, &self.start);
+        used.insert(&self.start);
+        used.into_iter()
+            .any(|name| self.reachable(&references, name).contains(name))
+    }
+
+    // an equivalent regular expression, only possible for grammars that aren't
+    // recursive and only reference rules that exist
+    pub fn to_regex(&self) -> Option<String> {
+        if self.is_recursive() {
+            return None;
+        }
+        let mut regex = String::from("^");
+        self.write_regex(&self.start, &mut regex)?;
+        regex.push('$');
+        Some(regex)
+    }
+
+    // rules are inlined, grouping them only when they have more than one alternative
+    fn write_regex(&self, name: &str, out: &mut String) -> Option<()> {
+        let alternatives = self.rules.get(name)?;
+        let group = alternatives.len() > 1;
+        if group {
+            out.push_str("(?:");
+        }
+        for (idx, alternative) in alternatives.iter().enumerate() {
+            if idx > 0 {
+                out.push('|');
+            }
+            for term in alternative {
+                match term {
+                    Term::Literal(literal) => {
+                        for c in literal.chars() {
+                            if "\\.+*?()|[]{}^$".contains(c) {
+                                out.push('\\');
+                            }
+                            out.push(c);
+                        }
+                    }
+                    Term::Class(class) => out.push_str(&class.to_string()),
+                    Term::Rule(inner) => self.write_regex(inner, out)?,
+                }
+            }
+        }
+        if group {
+            out.push(')');
+        }
+        Some(())
+    }
+
+    // rule a references rule b if b appears in any of its alternatives
+    fn references(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+        let mut references: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for (name, alternatives) in &self.rules {
+            let entry = references.entry(name.as_str()).or_default();
+            for term in alternatives.iter().flatten() {
+                if let Term::Rule(inner) = term {
+                    entry.insert(inner.as_str());
+                }
+            }
+        }
+        references
+    }
+
+    // every rule reachable in one or more steps from `from`
+    fn reachable<'a>(
+        &self,
+        edges: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+        from: &str,
+    ) -> BTreeSet<&'a str> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&str> = edges.get(from).into_iter().flatten().copied().collect();
+        while let Some(name) = stack.pop() {
+            if seen.insert(name) {
+                stack.extend(edges.get(name).into_iter().flatten().copied());
+            }
+        }
+        seen
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTree {
+    Leaf(String),
+    Node {
+        rule: String,
+        children: Vec<ParseTree>,
+    },
+}
+
+// written as (rule children...), with leaves quoted
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseTree::Leaf(text) => write!(f, "{:?}", text),
+            ParseTree::Node { rule, children } => {
+                write!(f, "({}", rule)?;
+                for child in children {
+                    write!(f, " {}", child)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CharClass, Diagnostic, Grammar, SyntaxError, Term};
+    use crate::day19::matcher::Matcher;
+
+    #[test]
+    fn unterminated() {
+        let error = |line, reason| Err(SyntaxError { line, reason });
+        assert_eq!(
+            Grammar::from_text("a: \"x\"\nb: \"abc", "a").map(|_| ()),
+            error(2, "unterminated literal")
+        );
+        assert_eq!(
+            Grammar::from_text("a: [ab", "a").map(|_| ()),
+            error(1, "unterminated character class")
+        );
+        assert_eq!(
+            Grammar::from_text("a: [ab\\]", "a").map(|_| ()),
+            error(1, "unterminated character class")
+        );
+        assert_eq!(
+            Grammar::from_text("a: [z-a]", "a").map(|_| ()),
+            error(1, "invalid character class")
+        );
+    }
+
+    #[test]
+    fn class_round_trip() {
+        let classes = vec![
+            CharClass {
+                ranges: vec![('a', 'z'), ('_', '_')],
+                negated: false,
+            },
+            CharClass {
+                ranges: vec![(']', ']'), ('\\', '\\'), ('[', '^'), ('-', '-')],
+                negated: true,
+            },
+            // a plain '^' that isn't first
+            CharClass {
+                ranges: vec![('^', '^')],
+                negated: false,
+            },
+        ];
+        for class in classes {
+            let text = format!("r: {}", class);
+            let grammar = Grammar::from_text(&text, "r").unwrap();
+            assert_eq!(
+                grammar.rules["r"],
+                vec![vec![Term::Class(class)]],
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn diagnostics() {
+        let text = "expr: expr \"+\" term | term\nterm: [0-9] digits\ndigits: | [0-9] digits\nlost: missing";
+        let grammar = Grammar::from_text(text, "expr").unwrap();
+        assert_eq!(
+            grammar.diagnostics(),
+            vec![
+                Diagnostic::UndefinedRule {
+                    rule: "lost".into(),
+                    reference: "missing".into()
+                },
+                Diagnostic::LeftRecursion("expr".into()),
+                Diagnostic::Unreachable("lost".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_tree() {
+        let text =
+            "greeting: \"hello\" sep name\nsep: \" \" | \"\"\nname: [A-Z] rest\nrest: | [^ ] rest";
+        let grammar = Grammar::from_text(text, "greeting").unwrap();
+        assert!(grammar.diagnostics().is_empty());
+
+        let matcher = Matcher::compile(&grammar).unwrap();
+        let message: Vec<char> = "helloBob".chars().collect();
+        let tree = matcher.parse(&message).unwrap();
+        assert_eq!(
+            tree.to_string(),
+            r#"(greeting "hello" (sep) (name "B" (rest "o" (rest "b" (rest)))))"#
+        );
+        assert!(matcher
+            .parse(&"hello bob".chars().collect::<Vec<_>>())
+            .is_none());
+    }
+
+    #[test]
+    fn regex() {
+        let text = "0: 4 1 5\n1: 2 3 | 3 2\n2: 4 4 | 5 5\n3: 4 5 | 5 4\n4: \"a\"\n5: \"b\"";
+        let grammar = Grammar::from_text(text, "0").unwrap();
+        assert_eq!(
+            grammar.to_regex().unwrap(),
+            "^a(?:(?:aa|bb)(?:ab|ba)|(?:ab|ba)(?:aa|bb))b$"
+        );
+        let recursive = Grammar::from_text("0: \"a\" | \"a\" 0", "0").unwrap();
+        assert_eq!(recursive.to_regex(), None);
+    }
+}
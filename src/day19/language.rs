@@ -0,0 +1,241 @@
+use std::collections::btree_map;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::grammar::{Grammar, Term};
+
+// nfa built by inlining every rule, edges with no character are epsilon moves
+struct Nfa {
+    edges: Vec<Vec<(Option<char>, usize)>>,
+}
+
+impl Nfa {
+    fn add(&mut self) -> usize {
+        self.edges.push(Vec::new());
+        self.edges.len() - 1
+    }
+
+    // adds rule `name` starting at state `from`, returns the state where it ends
+    fn build(&mut self, grammar: &Grammar, name: &str, from: usize) -> Option<usize> {
+        let end = self.add();
+        for alternative in grammar.rules.get(name)? {
+            let mut current = self.add();
+            self.edges[from].push((None, current));
+            for term in alternative {
+                match term {
+                    Term::Literal(literal) => {
+                        for c in literal.chars() {
+                            let next = self.add();
+                            self.edges[current].push((Some(c), next));
+                            current = next;
+                        }
+                    }
+                    Term::Class(class) => {
+                        // a negated class would need every other character
+                        if class.negated {
+                            return None;
+                        }
+                        let next = self.add();
+                        for &(low, high) in &class.ranges {
+                            for c in low..=high {
+                                self.edges[current].push((Some(c), next));
+                            }
+                        }
+                        current = next;
+                    }
+                    Term::Rule(inner) => {
+                        current = self.build(grammar, inner, current)?;
+                    }
+                }
+            }
+            self.edges[current].push((None, end));
+        }
+        Some(end)
+    }
+
+    fn closure(&self, states: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if seen.insert(state) {
+                for &(c, next) in &self.edges[state] {
+                    if c.is_none() {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        seen.into_iter().collect()
+    }
+}
+
+struct State {
+    accepting: bool,
+    transitions: BTreeMap<char, usize>,
+}
+
+// The finite language of a non recursive grammar, as a deterministic automaton.
+// Every string has exactly one path through it, so counting paths counts distinct
+// strings even when the grammar is ambiguous, and walking transitions in char
+// order lists strings in lexicographic order.
+pub struct Language {
+    states: Vec<State>,
+}
+
+impl Language {
+    // None if the grammar is recursive, references undefined rules or uses negated
+    // character classes, as those languages can't be listed
+    pub fn new(grammar: &Grammar) -> Option<Self> {
+        if grammar.is_recursive() {
+            return None;
+        }
+        let mut nfa = Nfa { edges: Vec::new() };
+        let start = nfa.add();
+        let accept = nfa.build(grammar, &grammar.start, start)?;
+
+        // subset construction, dfa state 0 is the start
+        let mut ids: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut subsets = vec![nfa.closure(vec![start])];
+        ids.insert(subsets[0].clone(), 0);
+        let mut states = Vec::new();
+        while states.len() < subsets.len() {
+            let subset = subsets[states.len()].clone();
+            let mut moves: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+            for &state in &subset {
+                for &(c, next) in &nfa.edges[state] {
+                    if let Some(c) = c {
+                        moves.entry(c).or_default().push(next);
+                    }
+                }
+            }
+            let mut transitions = BTreeMap::new();
+            for (c, targets) in moves {
+                let target = nfa.closure(targets);
+                let id = match ids.get(&target) {
+                    Some(&id) => id,
+                    None => {
+                        ids.insert(target.clone(), subsets.len());
+                        subsets.push(target);
+                        subsets.len() - 1
+                    }
+                };
+                transitions.insert(c, id);
+            }
+            states.push(State {
+                accepting: subset.contains(&accept),
+                transitions,
+            });
+        }
+
+        Some(Language { states })
+    }
+
+    pub fn count(&self) -> u128 {
+        let mut memo = vec![None; self.states.len()];
+        self.count_from(0, &mut memo)
+    }
+
+    // the automaton has no cycles, so this always ends
+    fn count_from(&self, state: usize, memo: &mut [Option<u128>]) -> u128 {
+        if let Some(count) = memo[state] {
+            return count;
+        }
+        let mut count = self.states[state].accepting as u128;
+        for &next in self.states[state].transitions.values() {
+            count += self.count_from(next, memo);
+        }
+        memo[state] = Some(count);
+        count
+    }
+
+    pub fn iter(&self) -> Strings<'_> {
+        Strings {
+            language: self,
+            stack: Vec::new(),
+            entering: Some(0),
+            current: String::new(),
+        }
+    }
+}
+
+// every string of a language, in lexicographic order
+pub struct Strings<'a> {
+    language: &'a Language,
+    stack: Vec<btree_map::Iter<'a, char, usize>>,
+    entering: Option<usize>,
+    current: String,
+}
+
+impl<'a> Iterator for Strings<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if let Some(state) = self.entering.take() {
+                let state = &self.language.states[state];
+                self.stack.push(state.transitions.iter());
+                if state.accepting {
+                    // a prefix comes before anything that extends it
+                    return Some(self.current.clone());
+                }
+            }
+            match self.stack.last_mut()?.next() {
+                Some((&c, &next)) => {
+                    self.current.push(c);
+                    self.entering = Some(next);
+                }
+                None => {
+                    self.stack.pop();
+                    self.current.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Language;
+    use crate::day19::grammar::Grammar;
+    use crate::day19::matcher::Matcher;
+
+    fn example() -> Grammar {
+        let input = include_str!("example");
+        Grammar::from_text(input.split("\n\n").next().unwrap(), "0").unwrap()
+    }
+
+    #[test]
+    fn enumerate() {
+        let grammar = example();
+        let language = Language::new(&grammar).unwrap();
+        let strings: Vec<String> = language.iter().collect();
+        assert_eq!(
+            strings,
+            vec!["aaaabb", "aaabab", "aabaab", "aabbbb", "abaaab", "ababbb", "abbabb", "abbbab"]
+        );
+        assert_eq!(language.count(), 8);
+
+        let matcher = Matcher::compile(&grammar).unwrap();
+        for s in strings {
+            assert!(matcher.matches(&s.chars().collect::<Vec<_>>()));
+        }
+    }
+
+    #[test]
+    fn ambiguous() {
+        // "ab" can be derived in two ways, but is only one string
+        let text = "0: a b | \"ab\" | \"a\"\na: \"a\"\nb: \"b\" | \"\"";
+        let grammar = Grammar::from_text(text, "0").unwrap();
+        let language = Language::new(&grammar).unwrap();
+        assert_eq!(language.count(), 2);
+        assert_eq!(language.iter().collect::<Vec<_>>(), vec!["a", "ab"]);
+    }
+
+    #[test]
+    fn input() {
+        let input = include_str!("input");
+        let grammar = Grammar::from_text(input.split("\n\n").next().unwrap(), "0").unwrap();
+        let language = Language::new(&grammar).unwrap();
+        // 42 and 31 each match half of the 256 strings of length 8
+        assert_eq!(language.count(), 128 * 128 * 128);
+    }
+}
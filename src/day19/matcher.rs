@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use super::grammar::{CharClass, Diagnostic, Grammar, ParseTree, Term};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Symbol {
+    Literal(Vec<char>),
+    Class(CharClass),
+    Rule(usize), // index into Matcher::alternatives
+}
+
+#[derive(Clone)]
+enum Memo {
+    Unknown,
+    Growing(Vec<usize>), // being computed, holds the ends found so far
+    Done(Vec<usize>),
+}
+
+// The grammar compiled once into dense vectors, which is then used as a memoised
+// recognizer. Instead of expanding a queue of pending rules, we ask "starting at
+// position p, at which positions can rule r end?", and every (rule, position) pair
+// is only answered once per message.
+//
+// Loops are handled generically: right and middle recursion (8: 42 8, 11: 42 11 31)
+// always consume a character before looping, so they are asked at a different
+// position. Left recursion (0: 0 1) is asked at the same position while still being
+// computed, in that case we answer with the ends found so far and recompute until
+// they stop growing.
+pub struct Matcher {
+    names: Vec<String>,
+    alternatives: Vec<Vec<Vec<Symbol>>>,
+    start: usize,
+}
+
+impl Matcher {
+    // fails on the first reference to a rule that doesn't exist
+    pub fn compile(grammar: &Grammar) -> Result<Self, Diagnostic> {
+        let names: Vec<String> = grammar.rules.keys().cloned().collect();
+        let index: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let mut alternatives = Vec::new();
+        for (name, rule) in &grammar.rules {
+            let mut compiled = Vec::new();
+            for alternative in rule {
+                let mut symbols = Vec::new();
+                for term in alternative {
+                    symbols.push(match term {
+                        Term::Literal(literal) => Symbol::Literal(literal.chars().collect()),
+                        Term::Class(class) => Symbol::Class(class.clone()),
+                        Term::Rule(reference) => match index.get(reference.as_str()) {
+                            Some(&idx) => Symbol::Rule(idx),
+                            None => {
+                                return Err(Diagnostic::UndefinedRule {
+                                    rule: name.clone(),
+                                    reference: reference.clone(),
+                                })
+                            }
+                        },
+                    });
+                }
+                compiled.push(symbols);
+            }
+            alternatives.push(compiled);
+        }
+
+        let start = match index.get(grammar.start.as_str()) {
+            Some(&start) => start,
+            None => {
+                return Err(Diagnostic::UndefinedRule {
+                    rule: grammar.start.clone(),
+                    reference: grammar.start.clone(),
+                })
+            }
+        };
+
+        Ok(Matcher {
+            names,
+            alternatives,
+            start,
+        })
+    }
+
+    pub fn matches(&self, message: &[char]) -> bool {
+        let mut memo = self.memo(message);
+        let (ends, _) = self.ends(self.start, 0, message, &mut memo);
+        ends.contains(&message.len())
+    }
+
+    // one of the ways the whole message can be derived from the start rule
+    pub fn parse(&self, message: &[char]) -> Option<ParseTree> {
+        let mut memo = self.memo(message);
+        let mut stack = Vec::new();
+        self.tree(self.start, 0, message.len(), message, &mut memo, &mut stack)
+    }
+
+    fn memo(&self, message: &[char]) -> Vec<Memo> {
+        vec![Memo::Unknown; self.alternatives.len() * (message.len() + 1)]
+    }
+
+    // position after matching `symbol` at `pos`, for symbols that aren't rules
+    fn step(symbol: &Symbol, pos: usize, message: &[char]) -> Option<usize> {
+        match symbol {
+            Symbol::Literal(literal) => {
+                if message[pos..].starts_with(literal) {
+                    Some(pos + literal.len())
+                } else {
+                    None
+                }
+            }
+            Symbol::Class(class) => match message.get(pos) {
+                Some(&c) if class.contains(c) => Some(pos + 1),
+                _ => None,
+            },
+            Symbol::Rule(_) => unreachable!(),
+        }
+    }
+
+    // returns every position where `rule` can stop matching when started at `start`,
+    // plus the keys of growing memo entries that were read to find them
+    fn ends(
+        &self,
+        rule: usize,
+        start: usize,
+        message: &[char],
+        memo: &mut [Memo],
+    ) -> (Vec<usize>, Vec<usize>) {
+        let key = rule * (message.len() + 1) + start;
+        match &memo[key] {
+            Memo::Done(ends) => return (ends.clone(), Vec::new()),
+            Memo::Growing(ends) => return (ends.clone(), vec![key]),
+            Memo::Unknown => {}
+        }
+
+        memo[key] = Memo::Growing(Vec::new());
+        loop {
+            let (ends, mut depends) = self.expand(rule, start, message, memo);
+            let grew = match &memo[key] {
+                Memo::Growing(seed) => seed != &ends,
+                _ => unreachable!(),
+            };
+            let looped = depends.contains(&key);
+            if grew && looped {
+                memo[key] = Memo::Growing(ends);
+                continue;
+            }
+
+            depends.retain(|&k| k != key);
+            if depends.is_empty() {
+                memo[key] = Memo::Done(ends.clone());
+            } else {
+                // depends on an outer rule that is still growing, so this answer might be
+                // incomplete and can't be remembered
+                memo[key] = Memo::Unknown;
+            }
+            return (ends, depends);
+        }
+    }
+
+    fn expand(
+        &self,
+        rule: usize,
+        start: usize,
+        message: &[char],
+        memo: &mut [Memo],
+    ) -> (Vec<usize>, Vec<usize>) {
+        let mut result = Vec::new();
+        let mut depends = Vec::new();
+        for alternative in &self.alternatives[rule] {
+            let mut positions = vec![start];
+            for symbol in alternative {
+                let mut next = Vec::new();
+                for &pos in &positions {
+                    match *symbol {
+                        Symbol::Rule(inner) => {
+                            let (ends, inner_depends) = self.ends(inner, pos, message, memo);
+                            next.extend(ends);
+                            depends.extend(inner_depends);
+                        }
+                        _ => next.extend(Matcher::step(symbol, pos, message)),
+                    }
+                }
+                next.sort_unstable();
+                next.dedup();
+                positions = next;
+                if positions.is_empty() {
+                    break;
+                }
+            }
+            result.extend(positions);
+        }
+        result.sort_unstable();
+        result.dedup();
+        depends.sort_unstable();
+        depends.dedup();
+        (result, depends)
+    }
+
+    // derivation of rule matching exactly message[start..end]. `stack` holds the
+    // (rule, start, end) being derived, so loops that don't consume anything are skipped
+    fn tree(
+        &self,
+        rule: usize,
+        start: usize,
+        end: usize,
+        message: &[char],
+        memo: &mut [Memo],
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<ParseTree> {
+        if stack.contains(&(rule, start, end)) {
+            return None;
+        }
+        stack.push((rule, start, end));
+        let mut result = None;
+        for alternative in &self.alternatives[rule] {
+            if let Some(children) = self.fill(alternative, start, end, message, memo, stack) {
+                result = Some(ParseTree::Node {
+                    rule: self.names[rule].clone(),
+                    children,
+                });
+                break;
+            }
+        }
+        stack.pop();
+        result
+    }
+
+    // derivation of every symbol in `symbols` in order, matching exactly message[start..end]
+    fn fill(
+        &self,
+        symbols: &[Symbol],
+        start: usize,
+        end: usize,
+        message: &[char],
+        memo: &mut [Memo],
+        stack: &mut Vec<(usize, usize, usize)>,
+    ) -> Option<Vec<ParseTree>> {
+        let symbol = match symbols.first() {
+            Some(symbol) => symbol,
+            None if start == end => return Some(Vec::new()),
+            None => return None,
+        };
+
+        let candidates = match *symbol {
+            Symbol::Rule(inner) => self.ends(inner, start, message, memo).0,
+            _ => Matcher::step(symbol, start, message).into_iter().collect(),
+        };
+        for pos in candidates.into_iter().filter(|&pos| pos <= end) {
+            let child = match *symbol {
+                Symbol::Rule(inner) => match self.tree(inner, start, pos, message, memo, stack) {
+                    Some(tree) => tree,
+                    None => continue,
+                },
+                _ => ParseTree::Leaf(message[start..pos].iter().collect()),
+            };
+            if let Some(mut rest) = self.fill(&symbols[1..], pos, end, message, memo, stack) {
+                rest.insert(0, child);
+                return Some(rest);
+            }
+        }
+        None
+    }
+}
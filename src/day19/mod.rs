@@ -1,4 +1,7 @@
 mod day19;
+mod grammar;
+mod language;
+mod matcher;
 
 pub use day19::part1;
 pub use day19::part2;
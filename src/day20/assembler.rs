@@ -0,0 +1,454 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use aoc2020::{Grid, Orientation, Vector2};
+
+// sides in the same order as the old borders(), the opposite side is (side + 2) % 4
+pub const LEFT: usize = 0;
+pub const TOP: usize = 1;
+pub const RIGHT: usize = 2;
+pub const BOTTOM: usize = 3;
+
+// an edge as bits, '#' is a 1, read left to right for top and bottom and
+// top to bottom for left and right, so touching edges of neighbours are equal
+fn edge(grid: &Grid, orientation: Orientation, side: usize) -> u32 {
+    let n = grid.cols;
+    (0..n)
+        .map(|i| match side {
+            LEFT => Vector2::new(0, i),
+            TOP => Vector2::new(i, 0),
+            RIGHT => Vector2::new(n - 1, i),
+            _ => Vector2::new(i, n - 1),
+        })
+        .fold(0, |bits, pos| {
+            let c = grid.get(&orientation.source(pos, n, n));
+            bits << 1 | (c == '#') as u32
+        })
+}
+
+// the same edge read from either end, so it doesn't depend on orientation
+fn canonical(edge: u32, len: i64) -> u32 {
+    let reversed = edge.reverse_bits() >> (32 - len);
+    edge.min(reversed)
+}
+
+// every tile placed on the image, rows of (tile id, orientation)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub tiles: Vec<Vec<(usize, Orientation)>>,
+}
+
+impl Layout {
+    pub fn rows(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.tiles[0].len()
+    }
+
+    pub fn corners(&self) -> [usize; 4] {
+        let (last_row, last_col) = (self.rows() - 1, self.cols() - 1);
+        [
+            self.tiles[0][0].0,
+            self.tiles[0][last_col].0,
+            self.tiles[last_row][0].0,
+            self.tiles[last_row][last_col].0,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblyError {
+    Empty,
+    // not square, not the same size as the other tiles, too big for an edge to fit in
+    // a u32, or too small to have anything left once the borders are cut off
+    TileSize(usize),
+    // shares no edge with any other tile
+    Unconnected(usize),
+    // no rectangle of this many tiles where every neighbour's edges match
+    NoArrangement(usize),
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssemblyError::Empty => write!(f, "there are no tiles"),
+            AssemblyError::TileSize(id) => {
+                write!(f, "tile {} doesn't have the same size as the rest", id)
+            }
+            AssemblyError::Unconnected(id) => {
+                write!(f, "tile {} doesn't share an edge with any other tile", id)
+            }
+            AssemblyError::NoArrangement(tiles) => {
+                write!(f, "{} tiles can't be arranged into a rectangle", tiles)
+            }
+        }
+    }
+}
+
+pub struct Assembler {
+    tiles: HashMap<usize, Grid>,
+    size: i64,
+    // edges[id][orientation index][side]
+    edges: HashMap<usize, [[u32; 4]; 8]>,
+    // canonical edge to every tile that has it
+    index: HashMap<u32, Vec<usize>>,
+}
+
+impl Assembler {
+    pub fn new(tiles: HashMap<usize, Grid>) -> Result<Self, AssemblyError> {
+        let mut ids: Vec<usize> = tiles.keys().cloned().collect();
+        ids.sort_unstable();
+        if ids.is_empty() {
+            return Err(AssemblyError::Empty);
+        }
+        // the most common width, so a single odd tile is the one that gets reported.
+        // Going through the ids backwards makes the lowest id win a tie
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for id in ids.iter() {
+            *counts.entry(tiles[id].cols).or_default() += 1;
+        }
+        let size = ids
+            .iter()
+            .rev()
+            .map(|id| tiles[id].cols)
+            .max_by_key(|cols| counts[cols])
+            .unwrap();
+
+        let mut edges = HashMap::new();
+        let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+        for id in ids {
+            let grid = &tiles[&id];
+            if grid.cols != size || grid.rows != size || !(3..=32).contains(&size) {
+                return Err(AssemblyError::TileSize(id));
+            }
+            let mut oriented = [[0; 4]; 8];
+            for orientation in Orientation::ALL.iter() {
+                for (side, bits) in oriented[orientation.index()].iter_mut().enumerate() {
+                    *bits = edge(grid, *orientation, side);
+                }
+            }
+            // orienting a tile only moves or reverses its edges, one orientation is enough
+            for &bits in oriented[0].iter() {
+                let sharing = index.entry(canonical(bits, size)).or_default();
+                // a tile can have the same edge twice
+                if sharing.last() != Some(&id) {
+                    sharing.push(id);
+                }
+            }
+            edges.insert(id, oriented);
+        }
+
+        Ok(Assembler {
+            tiles,
+            size,
+            edges,
+            index,
+        })
+    }
+
+    fn edge(&self, id: usize, orientation: Orientation, side: usize) -> u32 {
+        self.edges[&id][orientation.index()][side]
+    }
+
+    // every tile with this edge, in any orientation
+    fn sharing(&self, edge: u32) -> &[usize] {
+        &self.index[&canonical(edge, self.size)]
+    }
+
+    fn unmatched(&self, id: usize, orientation: Orientation, side: usize) -> bool {
+        let edge = self.edge(id, orientation, side);
+        self.sharing(edge).iter().all(|&other| other == id)
+    }
+
+    // tries every rectangle the tiles could make, squarest first. Only rows <= cols,
+    // a taller layout is the same as a wide one turned on its side
+    pub fn assemble(&self) -> Result<Layout, AssemblyError> {
+        let mut ids: Vec<usize> = self.tiles.keys().cloned().collect();
+        ids.sort_unstable();
+        if ids.len() > 1 {
+            if let Some(&id) = ids.iter().find(|&&id| {
+                (0..4).all(|side| self.unmatched(id, Orientation::new(false, 0), side))
+            }) {
+                return Err(AssemblyError::Unconnected(id));
+            }
+        }
+
+        // the top left tile can be anything, but a tile with two outer edges
+        // in that spot is most likely right
+        let mut first: Vec<(usize, Orientation)> = ids
+            .iter()
+            .flat_map(|&id| Orientation::ALL.iter().map(move |&o| (id, o)))
+            .collect();
+        first.sort_by_key(|&(id, o)| !(self.unmatched(id, o, LEFT) && self.unmatched(id, o, TOP)));
+
+        let total = ids.len();
+        let mut shapes: Vec<(usize, usize)> = (1..=total)
+            .filter(|rows| total.is_multiple_of(*rows) && rows * rows <= total)
+            .map(|rows| (rows, total / rows))
+            .collect();
+        shapes.reverse();
+
+        for (rows, cols) in shapes {
+            let mut placed = Vec::with_capacity(total);
+            let mut used = HashSet::new();
+            if self.place(cols, &first, &mut placed, &mut used) {
+                let tiles = placed.chunks(cols).map(|row| row.to_vec()).collect();
+                debug_assert_eq!(placed.len(), rows * cols);
+                return Ok(Layout { tiles });
+            }
+        }
+        Err(AssemblyError::NoArrangement(total))
+    }
+
+    // fills the layout row by row, a tile has to match the right edge of the tile to its
+    // left and the bottom edge of the one above. When an edge is shared by more than two
+    // tiles every option is tried, going back when a later spot can't be filled
+    fn place(
+        &self,
+        cols: usize,
+        first: &[(usize, Orientation)],
+        placed: &mut Vec<(usize, Orientation)>,
+        used: &mut HashSet<usize>,
+    ) -> bool {
+        let at = placed.len();
+        if at == self.tiles.len() {
+            return true;
+        }
+
+        let left = if !at.is_multiple_of(cols) {
+            let (id, orientation) = placed[at - 1];
+            Some(self.edge(id, orientation, RIGHT))
+        } else {
+            None
+        };
+        let top = if at >= cols {
+            let (id, orientation) = placed[at - cols];
+            Some(self.edge(id, orientation, BOTTOM))
+        } else {
+            None
+        };
+
+        let candidates: Vec<(usize, Orientation)> = match left.or(top) {
+            Some(edge) => self
+                .sharing(edge)
+                .iter()
+                .flat_map(|&id| Orientation::ALL.iter().map(move |&o| (id, o)))
+                .collect(),
+            None => first.to_vec(),
+        };
+
+        for (id, orientation) in candidates {
+            if used.contains(&id)
+                || left.is_some_and(|edge| self.edge(id, orientation, LEFT) != edge)
+                || top.is_some_and(|edge| self.edge(id, orientation, TOP) != edge)
+            {
+                continue;
+            }
+            placed.push((id, orientation));
+            used.insert(id);
+            if self.place(cols, first, placed, used) {
+                return true;
+            }
+            placed.pop();
+            used.remove(&id);
+        }
+        false
+    }
+
+    // every tile of the layout without its border, joined into a single grid
+    pub fn image(&self, layout: &Layout) -> Grid {
+        let inner = self.size - 2;
+        let cols = layout.cols() as i64 * inner;
+        let rows = layout.rows() as i64 * inner;
+        let mut image = Grid {
+            data: vec!['.'; (cols * rows) as usize],
+            cols,
+            rows,
+        };
+
+        for (tile_y, row) in layout.tiles.iter().enumerate() {
+            for (tile_x, &(id, orientation)) in row.iter().enumerate() {
+                let grid = &self.tiles[&id];
+                for y in 0..inner {
+                    for x in 0..inner {
+                        let source =
+                            orientation.source(Vector2::new(x + 1, y + 1), self.size, self.size);
+                        let position =
+                            Vector2::new(tile_x as i64 * inner + x, tile_y as i64 * inner + y);
+                        image.replace(&position, grid.get(&source));
+                    }
+                }
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Assembler, AssemblyError, Layout};
+    use aoc2020::{Grid, Orientation, Vector2};
+    use std::collections::HashMap;
+
+    fn random_picture(cols: i64, rows: i64, mut seed: u64) -> Grid {
+        let data = (0..cols * rows)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                if seed % 2 == 0 {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        Grid { data, cols, rows }
+    }
+
+    // a picture cut into rows x cols tiles where neighbours share their border, like in
+    // the puzzle, and every tile turned some way
+    fn cut(picture: &Grid, rows: i64, cols: i64, size: i64) -> HashMap<usize, Grid> {
+        let mut tiles = HashMap::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                let mut data = Vec::new();
+                for y in 0..size {
+                    for x in 0..size {
+                        let pos = Vector2::new(c * (size - 1) + x, r * (size - 1) + y);
+                        data.push(picture.get(&pos));
+                    }
+                }
+                let tile = Grid {
+                    data,
+                    cols: size,
+                    rows: size,
+                };
+                let id = (1000 + r * cols + c) as usize;
+                tiles.insert(id, Orientation::ALL[id * 3 % 8].apply(&tile));
+            }
+        }
+        tiles
+    }
+
+    // the picture without any tile borders
+    fn inner(picture: &Grid, size: i64) -> Grid {
+        let mut data = Vec::new();
+        for y in 0..picture.rows {
+            for x in 0..picture.cols {
+                if x % (size - 1) != 0 && y % (size - 1) != 0 {
+                    data.push(picture.get(&Vector2::new(x, y)));
+                }
+            }
+        }
+        let tiles = |len| (len - 1) / (size - 1);
+        Grid {
+            data,
+            cols: tiles(picture.cols) * (size - 2),
+            rows: tiles(picture.rows) * (size - 2),
+        }
+    }
+
+    fn puzzle(rows: i64, cols: i64, size: i64, seed: u64) -> (HashMap<usize, Grid>, Grid) {
+        let picture = random_picture(cols * (size - 1) + 1, rows * (size - 1) + 1, seed);
+        (cut(&picture, rows, cols, size), inner(&picture, size))
+    }
+
+    fn assemble(tiles: HashMap<usize, Grid>) -> Result<(Layout, Grid), AssemblyError> {
+        let assembler = Assembler::new(tiles)?;
+        let layout = assembler.assemble()?;
+        let image = assembler.image(&layout);
+        Ok((layout, image))
+    }
+
+    fn same_picture(a: &Grid, b: &Grid) -> bool {
+        Orientation::ALL
+            .iter()
+            .any(|o| o.size(a.cols, a.rows) == (b.cols, b.rows) && o.apply(a).data == b.data)
+    }
+
+    #[test]
+    fn rectangle() {
+        let (tiles, picture) = puzzle(3, 5, 12, 7);
+        let (layout, image) = assemble(tiles).unwrap();
+        assert_eq!((layout.rows(), layout.cols()), (3, 5));
+        assert!(same_picture(&picture, &image));
+
+        let (tiles, picture) = puzzle(4, 1, 12, 11);
+        let (layout, image) = assemble(tiles).unwrap();
+        assert_eq!((layout.rows(), layout.cols()), (1, 4));
+        assert!(same_picture(&picture, &image));
+    }
+
+    #[test]
+    fn ambiguous_edges() {
+        let (rows, cols, size) = (3, 3, 12);
+        let mut picture = random_picture(cols * (size - 1) + 1, rows * (size - 1) + 1, 3);
+        // every horizontal border gets the same pattern, so each bottom edge
+        // matches the top edge of every tile in the next row
+        for y in (0..picture.rows).step_by(size as usize - 1) {
+            for x in 0..picture.cols {
+                let c = if x % (size - 1) % 3 == 0 { '#' } else { '.' };
+                picture.replace(&Vector2::new(x, y), c);
+            }
+        }
+
+        let assembler = Assembler::new(cut(&picture, rows, cols, size)).unwrap();
+        assert!(assembler.index.values().any(|sharing| sharing.len() > 2));
+        let layout = assembler.assemble().unwrap();
+        assert!(same_picture(
+            &inner(&picture, size),
+            &assembler.image(&layout)
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Assembler::new(HashMap::new()).err(),
+            Some(AssemblyError::Empty)
+        );
+
+        let (mut tiles, _) = puzzle(2, 3, 12, 5);
+        tiles.remove(&1004);
+        assert_eq!(
+            assemble(tiles.clone()).err(),
+            Some(AssemblyError::NoArrangement(5))
+        );
+
+        let (stray, _) = puzzle(1, 1, 12, 99);
+        tiles.insert(1, stray[&1000].clone());
+        assert_eq!(
+            assemble(tiles.clone()).err(),
+            Some(AssemblyError::Unconnected(1))
+        );
+
+        let (small, _) = puzzle(1, 1, 10, 5);
+        tiles.insert(2, small[&1000].clone());
+        assert_eq!(
+            assemble(tiles.clone()).err(),
+            Some(AssemblyError::TileSize(2))
+        );
+        // the odd one out is blamed even when it has the lowest id
+        tiles.remove(&2);
+        tiles.insert(0, small[&1000].clone());
+        assert_eq!(assemble(tiles).err(), Some(AssemblyError::TileSize(0)));
+
+        // nothing is left of these without their borders
+        for &size in [0, 1, 2].iter() {
+            let tile = Grid {
+                data: vec!['#'; size * size],
+                cols: size as i64,
+                rows: size as i64,
+            };
+            let tiles: HashMap<usize, Grid> =
+                vec![(7, tile.clone()), (8, tile)].into_iter().collect();
+            assert_eq!(
+                Assembler::new(tiles).err(),
+                Some(AssemblyError::TileSize(7))
+            );
+        }
+    }
+}
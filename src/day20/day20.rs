@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 
-use aoc2020::{parse_grid, Grid, Vector2};
+use aoc2020::image::{self, Image, Palette, RED};
+use aoc2020::pattern::{mark, Match, Pattern};
+use aoc2020::{parse_grid, Grid};
+
+use super::assembler::Assembler;
 
 fn parse_input(input: &str) -> HashMap<usize, Grid> {
     let mut res = HashMap::new();
@@ -18,249 +22,33 @@ fn parse_input(input: &str) -> HashMap<usize, Grid> {
     res
 }
 
-fn borders(grid: &Grid) -> Vec<Vec<char>> {
-    let mut left = Vec::new();
-    for j in 0..grid.rows {
-        left.push(grid.get(&Vector2::new(0, j))); // left border
-    }
-
-    let mut top = Vec::new();
-    for i in 0..grid.cols {
-        top.push(grid.get(&Vector2::new(i, 0))); // top row border
-    }
-
-    let mut right = Vec::new();
-    for j in 0..grid.cols {
-        right.push(grid.get(&Vector2::new(grid.cols - 1, j))); // right
-    }
-
-    let mut bot = Vec::new();
-    for i in 0..grid.cols {
-        bot.push(grid.get(&Vector2::new(i, grid.rows - 1))); // bottom border
-    }
-
-    vec![left, top, right, bot]
-}
-
-fn flip(grid: &Grid) -> Grid {
-    let mut result = grid.clone();
-    for row in 0..grid.rows {
-        for col in 0..grid.cols {
-            let new_col = grid.cols - 1 - col;
-            let value = grid.get(&Vector2::new(col, row));
-            result.replace(&Vector2::new(new_col, row), value);
-        }
-    }
-    result
-}
-
-fn rotate(grid: &Grid) -> Grid {
-    let mut result = grid.clone();
-    for row in 0..grid.rows {
-        let new_col = grid.cols - 1 - row;
-        for col in 0..grid.cols {
-            let value = grid.get(&Vector2::new(col, row));
-            result.replace(&Vector2::new(new_col, col), value);
-        }
-    }
-    result
-}
-
-#[derive(Debug, Clone)]
-struct Tile {
-    id: usize,
-    grid: Grid,
-    connections: [Option<usize>; 4], // left, top, right, bottom connection indices
-                                     // these sides match with result order of fn borders(grid)
-}
-
-impl Tile {
-    fn new(id: usize, grid: Grid) -> Tile {
-        Self {
-            id,
-            grid,
-            connections: [None, None, None, None],
-        }
+const MONSTER: &str = concat!(
+    "                  # \n",
+    "#    ##    ##    ###\n",
+    " #  #  #  #  #  #   \n",
+);
+
+// the picture is only right in one orientation, the one with the most monsters. A
+// monster shaped pattern in any other orientation is just a coincidence
+fn find_monsters(grid: &Grid) -> Vec<Match> {
+    let mut monsters = Pattern::parse(MONSTER, ' ').find(grid);
+    let mut counts = [0; 8];
+    for monster in monsters.iter() {
+        counts[monster.orientation.index()] += 1;
     }
-}
-
-fn connect_all(mut all_grids: HashMap<usize, Grid>) -> HashMap<usize, Tile> {
-    let mut mappings = HashMap::new();
-
-    // place any tile in any starting orientation, all other tiles will rotate to match it
-    // so the first tile's starting orientation doesn't matter
-    // tiles that have already been placed can't be rotated or flipped
-    let (&key, first_grid) = all_grids.iter().next().unwrap();
-    let first_tile = Tile::new(key, first_grid.clone());
-    mappings.insert(key, first_tile);
-
-    let mut unfinished = Vec::new();
-    unfinished.push(key);
-
-    while let Some(current_id) = unfinished.pop() {
-        let current_tile = mappings.get(&current_id).unwrap().clone();
-        let current_borders = borders(&current_tile.grid);
-
-        // count how many connections the tile already has
-        let mut connections_matched = current_tile
-            .connections
-            .iter()
-            .filter(|o| o.is_some())
-            .count();
-        'outer: for (&other_id, other_grid) in all_grids.iter_mut() {
-            if other_id == current_id {
-                continue;
-            }
-            if current_tile.connections.contains(&Some(other_id)) {
-                continue;
-            }
-            if connections_matched == 4 {
-                break;
-            }
-
-            'inner: for (side, connection) in current_tile.connections.iter().enumerate() {
-                if connection.is_none() {
-                    for _ in 0..2 {
-                        // unflipped/ flipped once
-                        for _ in 0..4 {
-                            // rotated 0, 1, 2, 3 times.
-                            let other_borders = borders(other_grid);
-                            // the reverse border which current_tile must match with
-                            let other_side = (side + 2) % 4;
-                            if current_borders[side] == other_borders[other_side] {
-                                // check if other_tile is already mapped, otherwise add it to mappings
-                                if let Some(other_tile) = mappings.get_mut(&other_id) {
-                                    other_tile.connections[other_side] = Some(current_id);
-                                } else {
-                                    let mut new_tile = Tile::new(other_id, other_grid.clone());
-                                    new_tile.connections[other_side] = Some(current_id);
-                                    mappings.insert(other_id, new_tile);
-                                    unfinished.push(other_id);
-                                }
-
-                                mappings.get_mut(&current_id).unwrap().connections[side] =
-                                    Some(other_id);
-                                connections_matched += 1;
-
-                                continue 'outer; // current_tile connected to other_tile; continue to other tiles
-                            }
-                            if mappings.contains_key(&other_id) {
-                                continue 'inner; // we cant rotate/flip this tile as it's already in the mapping,
-                                                 // but other sides might still match
-                            }
-                            *other_grid = rotate(&other_grid);
-                        }
-                        *other_grid = flip(&other_grid);
-                    }
-                }
-            }
-        }
-    }
-
-    mappings
+    // the first orientation on a tie
+    let best = (0..8).rev().max_by_key(|&idx| counts[idx]).unwrap();
+    monsters.retain(|monster| monster.orientation.index() == best);
+    monsters
 }
 
 pub fn part1(input: &str) -> usize {
     let tiles = parse_input(input);
 
-    let mappings = connect_all(tiles);
-
-    mappings
-        .iter()
-        .filter(|(_, tile)| tile.connections.iter().filter(|o| o.is_some()).count() == 2) // find tiles with two connections
-        .map(|(&id, _)| id)
-        .product()
-}
-
-// assemble all mapped tiles into a single big grid
-fn join_tiles(mappings: HashMap<usize, Tile>) -> Grid {
-    let size = mappings.values().next().unwrap().grid.cols - 2;
-    let side_amount = (mappings.len() as f32).sqrt() as i64;
-    let colsrows = size * side_amount;
-    let map_size = colsrows * colsrows;
+    let assembler = Assembler::new(tiles).unwrap();
+    let layout = assembler.assemble().unwrap();
 
-    let data = vec!['.'; map_size as usize];
-    let mut final_grid = Grid {
-        data,
-        cols: colsrows,
-        rows: colsrows,
-    };
-
-    let mut left = mappings
-        .values()
-        .filter(|t| {
-            t.connections[0].is_none()
-                && t.connections[1].is_none()
-                && t.connections[2].is_some()
-                && t.connections[3].is_some()
-        })
-        .next()
-        .unwrap(); // find the top left tile
-    for tile_j in 0..side_amount {
-        let mut current = left.clone();
-        for tile_i in 0..side_amount {
-            for y in 1..size + 1 {
-                for x in 1..size + 1 {
-                    let c = current.grid.get(&Vector2::new(x, y));
-                    let big_position = Vector2::new(tile_i * size + x - 1, tile_j * size + y - 1);
-                    final_grid.replace(&big_position, c);
-                }
-            }
-            // move current tile to next right tile
-            if let Some(connection) = &current.connections[2] {
-                current = mappings[connection].clone();
-            }
-        }
-        // move leftmost tile down one row
-        if let Some(connection) = &left.connections[3] {
-            left = &mappings[connection];
-        }
-    }
-    final_grid
-}
-
-fn find_monsters(mut grid: Grid) -> usize {
-    let monsters: [Vector2; 15] = [
-        Vector2::new(0, 1),
-        Vector2::new(1, 2),
-        Vector2::new(4, 2),
-        Vector2::new(5, 1),
-        Vector2::new(6, 1),
-        Vector2::new(7, 2),
-        Vector2::new(10, 2),
-        Vector2::new(11, 1),
-        Vector2::new(12, 1),
-        Vector2::new(13, 2),
-        Vector2::new(16, 2),
-        Vector2::new(17, 1),
-        Vector2::new(18, 1),
-        Vector2::new(19, 1),
-        Vector2::new(18, 0),
-    ];
-
-    for _ in 0..2 {
-        for _ in 0..4 {
-            let mut found_count = 0;
-            for j in 0..grid.rows - 2 {
-                for i in 0..grid.cols - 19 {
-                    let current = Vector2::new(i, j);
-                    if monsters
-                        .iter()
-                        .map(|v| *v + current)
-                        .all(|v| grid.get(&v) == '#')
-                    {
-                        found_count += 1;
-                    }
-                }
-            }
-            if found_count > 0 {
-                return found_count;
-            }
-            grid = rotate(&grid);
-        }
-        grid = flip(&grid);
-    }
-    0
+    layout.corners().iter().product()
 }
 
 fn count_tiles(grid: &Grid) -> usize {
@@ -270,16 +58,36 @@ fn count_tiles(grid: &Grid) -> usize {
 pub fn part2(input: &str) -> usize {
     let tiles = parse_input(input);
 
-    let mappings = connect_all(tiles);
-    let final_grid = join_tiles(mappings);
+    let assembler = Assembler::new(tiles).unwrap();
+    let layout = assembler.assemble().unwrap();
+    let final_grid = assembler.image(&layout);
+
+    // monsters can overlap, so count what's left after marking them instead of
+    // subtracting 15 for each one
+    let monsters = find_monsters(&final_grid);
+    count_tiles(&mark(&final_grid, &monsters, 'O'))
+}
 
-    let total_tiles = count_tiles(&final_grid);
-    let monsters = find_monsters(final_grid);
-    total_tiles - (monsters * 15)
+// the assembled picture with every sea monster in red
+pub fn picture(input: &str) -> Image {
+    let assembler = Assembler::new(parse_input(input)).unwrap();
+    let layout = assembler.assemble().unwrap();
+    let final_grid = assembler.image(&layout);
+
+    let monsters = find_monsters(&final_grid);
+    let mut picture = image::from_grid(&final_grid, &Palette::default());
+    image::highlight(
+        &mut picture,
+        monsters.iter().flat_map(|m| m.cells.iter()),
+        RED,
+    );
+    picture
 }
 
 #[cfg(test)]
 mod test {
+    use super::{Assembler, Pattern};
+
     #[test]
     fn part1() {
         let input = include_str!("input");
@@ -291,4 +99,68 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 2409);
     }
+
+    #[test]
+    fn example() {
+        let input = include_str!("example");
+        assert_eq!(super::part1(input), 20899048083289);
+        assert_eq!(super::part2(input), 273);
+    }
+
+    #[test]
+    fn monsters() {
+        let assembler = Assembler::new(super::parse_input(include_str!("example"))).unwrap();
+        let image = assembler.image(&assembler.assemble().unwrap());
+        let monsters = Pattern::parse(super::MONSTER, ' ').find(&image);
+        assert_eq!(monsters.len(), 2);
+        assert!(monsters
+            .iter()
+            .all(|m| m.orientation == monsters[0].orientation));
+        assert!(monsters.iter().all(|m| m.cells.len() == 15));
+    }
+
+    #[test]
+    fn one_orientation() {
+        // two monsters, and a mirrored one in between
+        let mirrored: String = super::MONSTER
+            .lines()
+            .map(|line| line.chars().rev().collect::<String>() + "\n")
+            .collect();
+        let text = [super::MONSTER, &mirrored, super::MONSTER].concat();
+        let grid = aoc2020::parse_grid(&text);
+        assert_eq!(Pattern::parse(super::MONSTER, ' ').find(&grid).len(), 3);
+
+        let monsters = super::find_monsters(&grid);
+        assert_eq!(monsters.len(), 2);
+        assert!(monsters.iter().all(|m| m.orientation.index() == 0));
+    }
+
+    #[test]
+    fn picture() {
+        let picture = super::picture(include_str!("example"));
+        assert_eq!((picture.width, picture.height), (24, 24));
+        let red = picture.pixels.iter().filter(|&&p| p == super::RED).count();
+        assert_eq!(red, 30);
+    }
+
+    #[test]
+    fn layout() {
+        let assembler = Assembler::new(super::parse_input(include_str!("example"))).unwrap();
+        let layout = assembler.assemble().unwrap();
+        assert_eq!((layout.rows(), layout.cols()), (3, 3));
+
+        let mut corners = layout.corners();
+        corners.sort_unstable();
+        assert_eq!(corners, [1171, 1951, 2971, 3079]);
+        // 2311 is in the middle of an outer side, whichever way the image ends up
+        let middle: Vec<usize> = layout.tiles.iter().map(|row| row[1].0).collect();
+        let edges = [
+            middle[0],
+            middle[2],
+            layout.tiles[1][0].0,
+            layout.tiles[1][2].0,
+        ];
+        assert!(edges.contains(&2311));
+        assert_eq!(layout.tiles[1][1].0, 1427);
+    }
 }
@@ -1,3 +1,4 @@
+mod assembler;
 mod day20;
 
 pub use day20::part1;
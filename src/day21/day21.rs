@@ -1,4 +1,5 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use aoc2020::matching::matching;
+use std::collections::{HashMap, HashSet};
 
 fn parse_input(input: &str) -> Vec<(Vec<&str>, Option<Vec<&str>>)> {
     let mut res = Vec::new();
@@ -62,33 +63,12 @@ pub fn part1(input: &str) -> usize {
 
 pub fn part2(input: &str) -> String {
     let lines = parse_input(input);
-    let mut allergen_to_ingredient = find_allergens(&lines);
-
-    let mut canonical_list: BTreeMap<&str, &str> = BTreeMap::new();
-    while !allergen_to_ingredient.is_empty() {
-        for (&allergen, ingredients) in allergen_to_ingredient.clone().iter() {
-            // if this allergen can only match one ingredient, we can add it to canonical list
-            // and remove it from the rest of the elements
-            // this allows us to match allergens with ingredients one by one
-            if ingredients.len() == 1 {
-                let ingredient_matched = ingredients.iter().next().unwrap();
-                for (_, ingredients_mut) in allergen_to_ingredient.iter_mut() {
-                    ingredients_mut.remove(ingredient_matched);
-                }
-                canonical_list.insert(allergen, ingredient_matched);
-                allergen_to_ingredient.remove(allergen);
-                break;
-            }
-        }
-    }
+    let allergen_to_ingredient = find_allergens(&lines);
 
-    let mut result = String::new();
-    for (_, ingredient) in canonical_list {
-        result.push_str(ingredient);
-        result.push(',');
-    }
-    result.pop(); // remove last ','
-    result
+    // every allergen is in exactly one ingredient, and every ingredient has at most one allergen
+    let canonical_list = matching(allergen_to_ingredient).unwrap();
+    let ingredients: Vec<&str> = canonical_list.values().cloned().collect();
+    ingredients.join(",")
 }
 
 #[cfg(test)]
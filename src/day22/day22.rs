@@ -1,4 +1,4 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 fn parse_input(input: &str) -> (VecDeque<u8>, VecDeque<u8>) {
     let mut it = input.split("\n\n");
@@ -39,7 +39,7 @@ pub fn part1(input: &str) -> usize {
     }
 }
 
-fn score(deck: VecDeque<u8>) -> usize {
+pub fn score(deck: VecDeque<u8>) -> usize {
     deck.into_iter()
         .rev()
         .enumerate()
@@ -47,6 +47,7 @@ fn score(deck: VecDeque<u8>) -> usize {
         .sum()
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Winner {
     Player1,
     Player2,
@@ -101,16 +102,111 @@ fn recursive_combat(
     }
 }
 
+// the decks of a round packed into a single buffer, player 1's deck prefixed with
+// its length so no two rounds share a key
+fn pack_decks(deck_p1: &VecDeque<u8>, deck_p2: &VecDeque<u8>) -> Vec<u8> {
+    let mut key = Vec::with_capacity(4 + deck_p1.len() + deck_p2.len());
+    key.extend_from_slice(&(deck_p1.len() as u32).to_le_bytes());
+    key.extend(deck_p1);
+    key.extend(deck_p2);
+    key
+}
+
+// same game as recursive_combat, but:
+// - rounds are remembered in a single packed buffer instead of a clone of both decks
+// - sub-games are remembered by their starting decks, so the same sub-game is only played once
+// - when no two cards are the same, a sub-game where player 1 holds the highest card, and that
+//   card is at least the amount of cards in play, is won by player 1 right away. Any round
+//   where it's played can't recurse, player 1 has fewer cards left than its value, and it
+//   always wins, so the game either ends with player 1 holding every card or repeats, which
+//   player 1 wins. We don't need the final deck of a sub-game, so it doesn't have to be played
+fn fast_recursive_combat(deck_p1: VecDeque<u8>, deck_p2: VecDeque<u8>) -> (Winner, VecDeque<u8>) {
+    let mut seen = HashSet::new();
+    let distinct = deck_p1
+        .iter()
+        .chain(deck_p2.iter())
+        .all(|&card| seen.insert(card));
+    fast_game(deck_p1, deck_p2, false, distinct, &mut HashMap::new())
+}
+
+fn fast_game(
+    mut deck_p1: VecDeque<u8>,
+    mut deck_p2: VecDeque<u8>,
+    sub_game: bool,
+    distinct: bool,
+    memo: &mut HashMap<(VecDeque<u8>, VecDeque<u8>), Winner>,
+) -> (Winner, VecDeque<u8>) {
+    if sub_game && distinct {
+        let highest = deck_p1.iter().max();
+        let in_play = deck_p1.len() + deck_p2.len();
+        if highest > deck_p2.iter().max() && *highest.unwrap() as usize >= in_play {
+            return (Winner::Player1, deck_p1);
+        }
+    }
+
+    let mut played_set: HashSet<Vec<u8>> = HashSet::new();
+    while !(deck_p1.is_empty() || deck_p2.is_empty()) {
+        if !played_set.insert(pack_decks(&deck_p1, &deck_p2)) {
+            return (Winner::Player1, deck_p1);
+        }
+
+        let p1_card = deck_p1.pop_front().unwrap();
+        let p2_card = deck_p2.pop_front().unwrap();
+
+        let winner = if deck_p1.len() >= p1_card as usize && deck_p2.len() >= p2_card as usize {
+            let new_deck_p1: VecDeque<u8> =
+                deck_p1.iter().take(p1_card as usize).cloned().collect();
+            let new_deck_p2: VecDeque<u8> =
+                deck_p2.iter().take(p2_card as usize).cloned().collect();
+            let key = (new_deck_p1, new_deck_p2);
+            match memo.get(&key) {
+                Some(&winner) => winner,
+                None => {
+                    let (winner, _) = fast_game(key.0.clone(), key.1.clone(), true, distinct, memo);
+                    memo.insert(key, winner);
+                    winner
+                }
+            }
+        } else if p1_card > p2_card {
+            Winner::Player1
+        } else if p2_card > p1_card {
+            Winner::Player2
+        } else {
+            continue;
+        };
+
+        match winner {
+            Winner::Player1 => {
+                deck_p1.push_back(p1_card);
+                deck_p1.push_back(p2_card);
+            }
+            Winner::Player2 => {
+                deck_p2.push_back(p2_card);
+                deck_p2.push_back(p1_card);
+            }
+        };
+    }
+
+    if deck_p1.is_empty() {
+        (Winner::Player2, deck_p2)
+    } else {
+        (Winner::Player1, deck_p1)
+    }
+}
+
 pub fn part2(input: &str) -> usize {
     let (player1, player2) = parse_input(input);
 
-    let (_, deck) = recursive_combat(player1, player2);
+    let (_, deck) = fast_recursive_combat(player1, player2);
 
     score(deck)
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2020::XorShift;
+    use std::collections::VecDeque;
+
     #[test]
     fn part1() {
         let input = include_str!("input");
@@ -122,4 +218,27 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 32519);
     }
+
+    #[test]
+    fn fast_matches_recursive() {
+        for input in [include_str!("input"), include_str!("example")].iter() {
+            let (player1, player2) = super::parse_input(input);
+            let expected = super::recursive_combat(player1.clone(), player2.clone());
+            let fast = super::fast_recursive_combat(player1, player2);
+            assert_eq!(fast, expected);
+        }
+    }
+
+    #[test]
+    fn duplicate_cards() {
+        // small cards that repeat, so sub-games are common and the shortcut can't be used
+        for seed in 1..=200 {
+            let mut cards = XorShift::new(seed).map(|n| 1 + (n % 5) as u8);
+            let player1: VecDeque<u8> = cards.by_ref().take(7).collect();
+            let player2: VecDeque<u8> = cards.take(7).collect();
+            let expected = super::recursive_combat(player1.clone(), player2.clone());
+            let fast = super::fast_recursive_combat(player1, player2);
+            assert_eq!(fast, expected, "seed {}", seed);
+        }
+    }
 }
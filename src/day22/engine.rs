@@ -0,0 +1,426 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+// what happens to the cards when nobody played the single highest card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tie {
+    Discard, // the played cards leave the game
+    Return,  // every card goes back to the bottom of its owner's deck
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub recursive: bool,
+    pub tie: Tie,
+}
+
+impl Rules {
+    pub const COMBAT: Rules = Rules {
+        recursive: false,
+        tie: Tie::Discard,
+    };
+    pub const RECURSIVE_COMBAT: Rules = Rules {
+        recursive: true,
+        tie: Tie::Discard,
+    };
+}
+
+// players are indexes into the decks, shown to humans starting at 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    GameStart {
+        game: usize,
+    },
+    Round {
+        game: usize,
+        round: usize,
+        decks: Vec<Vec<u8>>,
+        played: Vec<(usize, u8)>,
+    },
+    SubGame {
+        game: usize,
+    },
+    BackTo {
+        game: usize,
+    },
+    // None if the round was a tie
+    RoundWinner {
+        game: usize,
+        round: usize,
+        player: Option<usize>,
+    },
+    // the decks are the same as a previous round of this game
+    Repeated {
+        game: usize,
+    },
+    // None if every card got discarded
+    GameWinner {
+        game: usize,
+        player: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub winner: Option<usize>,
+    pub decks: Vec<VecDeque<u8>>,
+}
+
+// Plays combat between any amount of players. Every round the active players (the ones
+// with cards left) play their top card, and the highest card wins all of them, with the
+// winner's card on top and the others sorted from highest to lowest.
+// With recursion on, a round where every player has at least as many cards left as the
+// card they played is decided by a sub-game. A game that repeats the decks of an
+// earlier round is won by player 1, otherwise recursion or returned ties could loop forever.
+pub struct Combat {
+    rules: Rules,
+    games: usize,
+    log: Option<Vec<Event>>,
+}
+
+pub fn parse_decks(input: &str) -> Vec<VecDeque<u8>> {
+    input
+        .trim()
+        .split("\n\n")
+        .map(|player| {
+            player
+                .lines()
+                .skip(1)
+                .map(|line| line.parse().unwrap())
+                .collect()
+        })
+        .collect()
+}
+
+impl Combat {
+    pub fn new(rules: Rules) -> Self {
+        Combat {
+            rules,
+            games: 0,
+            log: None,
+        }
+    }
+
+    // keeps every event, which can get big for long recursive games
+    pub fn with_log(rules: Rules) -> Self {
+        Combat {
+            rules,
+            games: 0,
+            log: Some(Vec::new()),
+        }
+    }
+
+    pub fn log(&self) -> &[Event] {
+        self.log.as_deref().unwrap_or(&[])
+    }
+
+    fn record(&mut self, event: Event) {
+        if let Some(log) = &mut self.log {
+            log.push(event);
+        }
+    }
+
+    pub fn play(&mut self, decks: Vec<VecDeque<u8>>) -> Outcome {
+        self.game(decks)
+    }
+
+    fn game(&mut self, mut decks: Vec<VecDeque<u8>>) -> Outcome {
+        self.games += 1;
+        let game = self.games;
+        self.record(Event::GameStart { game });
+
+        let mut seen = HashSet::new();
+        let mut round = 0;
+        let winner = loop {
+            let active: Vec<usize> = (0..decks.len())
+                .filter(|&player| !decks[player].is_empty())
+                .collect();
+            if active.len() <= 1 {
+                break active.first().copied();
+            }
+            if !seen.insert(decks.clone()) {
+                self.record(Event::Repeated { game });
+                break Some(0);
+            }
+
+            round += 1;
+            let played: Vec<(usize, u8)> = active
+                .iter()
+                .map(|&player| (player, decks[player].pop_front().unwrap()))
+                .collect();
+            if self.log.is_some() {
+                let mut before: Vec<Vec<u8>> =
+                    decks.iter().map(|d| d.iter().copied().collect()).collect();
+                for &(player, card) in &played {
+                    before[player].insert(0, card);
+                }
+                self.record(Event::Round {
+                    game,
+                    round,
+                    decks: before,
+                    played: played.clone(),
+                });
+            }
+
+            let recurse = self.rules.recursive
+                && played
+                    .iter()
+                    .all(|&(player, card)| decks[player].len() >= card as usize);
+            let winner = if recurse {
+                self.record(Event::SubGame { game });
+                let mut sub_decks = vec![VecDeque::new(); decks.len()];
+                for &(player, card) in &played {
+                    sub_decks[player] = decks[player].iter().take(card as usize).copied().collect();
+                }
+                let outcome = self.game(sub_decks);
+                self.record(Event::BackTo { game });
+                outcome.winner
+            } else {
+                let highest = played.iter().map(|&(_, card)| card).max().unwrap();
+                let mut with_highest = played.iter().filter(|&&(_, card)| card == highest);
+                match (with_highest.next(), with_highest.next()) {
+                    (Some(&(player, _)), None) => Some(player),
+                    _ => None,
+                }
+            };
+            self.record(Event::RoundWinner {
+                game,
+                round,
+                player: winner,
+            });
+
+            match winner {
+                Some(winner) => {
+                    let mut cards = played.clone();
+                    // winner's card first, then the rest from highest to lowest
+                    cards
+                        .sort_by_key(|&(player, card)| (player != winner, std::cmp::Reverse(card)));
+                    decks[winner].extend(cards.into_iter().map(|(_, card)| card));
+                }
+                None => {
+                    if self.rules.tie == Tie::Return {
+                        for (player, card) in played {
+                            decks[player].push_back(card);
+                        }
+                    }
+                }
+            }
+        };
+
+        self.record(Event::GameWinner {
+            game,
+            player: winner,
+        });
+        Outcome { winner, decks }
+    }
+
+    // the log written like the puzzle's examples
+    pub fn narrate(&self, outcome: &Outcome) -> String {
+        let join = |cards: &[u8]| {
+            cards
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let recursive = self.rules.recursive;
+        let mut out = String::new();
+        for event in self.log() {
+            match event {
+                Event::GameStart { game } => {
+                    if recursive {
+                        writeln!(out, "=== Game {} ===", game).unwrap();
+                    }
+                }
+                Event::Round {
+                    game,
+                    round,
+                    decks,
+                    played,
+                } => {
+                    if recursive {
+                        writeln!(out, "\n-- Round {} (Game {}) --", round, game).unwrap();
+                    } else {
+                        writeln!(out, "-- Round {} --", round).unwrap();
+                    }
+                    for (player, deck) in decks.iter().enumerate() {
+                        writeln!(out, "Player {}'s deck: {}", player + 1, join(deck)).unwrap();
+                    }
+                    for (player, card) in played {
+                        writeln!(out, "Player {} plays: {}", player + 1, card).unwrap();
+                    }
+                }
+                Event::SubGame { .. } => {
+                    writeln!(out, "Playing a sub-game to determine the winner...\n").unwrap();
+                }
+                Event::BackTo { game } => {
+                    writeln!(out, "\n...anyway, back to game {}.", game).unwrap();
+                }
+                Event::RoundWinner {
+                    game,
+                    round,
+                    player,
+                } => match (player, recursive) {
+                    (Some(player), true) => writeln!(
+                        out,
+                        "Player {} wins round {} of game {}!",
+                        player + 1,
+                        round,
+                        game
+                    )
+                    .unwrap(),
+                    (Some(player), false) => {
+                        writeln!(out, "Player {} wins the round!\n", player + 1).unwrap()
+                    }
+                    (None, true) => {
+                        writeln!(out, "Round {} of game {} is a tie!", round, game).unwrap()
+                    }
+                    (None, false) => writeln!(out, "The round is a tie!\n").unwrap(),
+                },
+                Event::Repeated { game } => {
+                    writeln!(out, "Game {} repeated a previous round!", game).unwrap();
+                }
+                Event::GameWinner { game, player } => {
+                    if recursive {
+                        match player {
+                            Some(player) => writeln!(
+                                out,
+                                "The winner of game {} is player {}!",
+                                game,
+                                player + 1
+                            )
+                            .unwrap(),
+                            None => writeln!(out, "Game {} has no winner!", game).unwrap(),
+                        }
+                    }
+                }
+            }
+        }
+
+        if recursive {
+            out.push_str("\n\n");
+        }
+        out.push_str("== Post-game results ==\n");
+        for (player, deck) in outcome.decks.iter().enumerate() {
+            let deck: Vec<u8> = deck.iter().copied().collect();
+            writeln!(out, "Player {}'s deck: {}", player + 1, join(&deck)).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_decks, Combat, Event, Rules, Tie};
+    use crate::day22::day22::score;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn combat_narration() {
+        let decks = parse_decks(include_str!("example"));
+        let mut combat = Combat::with_log(Rules::COMBAT);
+        let outcome = combat.play(decks);
+        assert_eq!(outcome.winner, Some(1));
+
+        let narration = combat.narrate(&outcome);
+        assert!(narration.starts_with(concat!(
+            "-- Round 1 --\n",
+            "Player 1's deck: 9, 2, 6, 3, 1\n",
+            "Player 2's deck: 5, 8, 4, 7, 10\n",
+            "Player 1 plays: 9\n",
+            "Player 2 plays: 5\n",
+            "Player 1 wins the round!\n\n",
+            "-- Round 2 --\n",
+        )));
+        assert!(narration.ends_with(concat!(
+            "Player 2 wins the round!\n\n",
+            "== Post-game results ==\n",
+            "Player 1's deck: \n",
+            "Player 2's deck: 3, 2, 10, 6, 8, 5, 9, 4, 7, 1\n",
+        )));
+    }
+
+    #[test]
+    fn recursive_narration() {
+        let decks = parse_decks(include_str!("example"));
+        let mut combat = Combat::with_log(Rules::RECURSIVE_COMBAT);
+        let outcome = combat.play(decks);
+
+        let narration = combat.narrate(&outcome);
+        assert!(narration.starts_with(concat!(
+            "=== Game 1 ===\n\n",
+            "-- Round 1 (Game 1) --\n",
+            "Player 1's deck: 9, 2, 6, 3, 1\n",
+            "Player 2's deck: 5, 8, 4, 7, 10\n",
+            "Player 1 plays: 9\n",
+            "Player 2 plays: 5\n",
+            "Player 1 wins round 1 of game 1!\n",
+        )));
+        assert!(narration.contains(concat!(
+            "Player 1 plays: 4\n",
+            "Player 2 plays: 3\n",
+            "Playing a sub-game to determine the winner...\n\n",
+            "=== Game 2 ===\n\n",
+            "-- Round 1 (Game 2) --\n",
+            "Player 1's deck: 9, 8, 5, 2\n",
+            "Player 2's deck: 10, 1, 7\n",
+        )));
+        assert!(narration.ends_with(concat!(
+            "The winner of game 1 is player 2!\n\n\n",
+            "== Post-game results ==\n",
+            "Player 1's deck: \n",
+            "Player 2's deck: 7, 5, 6, 2, 4, 1, 10, 8, 9, 3\n",
+        )));
+        let games = combat
+            .log()
+            .iter()
+            .filter(|event| matches!(event, Event::GameStart { .. }))
+            .count();
+        assert_eq!(games, 5);
+    }
+
+    #[test]
+    fn input() {
+        let decks = parse_decks(include_str!("input"));
+        let outcome = Combat::new(Rules::COMBAT).play(decks.clone());
+        assert_eq!(score(outcome.decks[0].clone()), 32629);
+
+        let outcome = Combat::new(Rules::RECURSIVE_COMBAT).play(decks);
+        let winner = outcome.winner.unwrap();
+        assert_eq!(score(outcome.decks[winner].clone()), 32519);
+    }
+
+    #[test]
+    fn three_players_and_ties() {
+        let decks: Vec<VecDeque<u8>> = vec![
+            vec![5, 1].into_iter().collect(),
+            vec![5, 2].into_iter().collect(),
+            vec![3].into_iter().collect(),
+        ];
+        let rules = Rules {
+            recursive: false,
+            tie: Tie::Return,
+        };
+        let mut combat = Combat::with_log(rules);
+        let outcome = combat.play(decks);
+        assert_eq!(
+            combat.log()[2],
+            Event::RoundWinner {
+                game: 1,
+                round: 1,
+                player: None
+            }
+        );
+        // round 2 gives player 3 the cards 3, 2, 1, then the 5s tie and return every
+        // round while player 3 cycles back to 3, 2, 1 and the game repeats
+        assert_eq!(
+            combat.log().last(),
+            Some(&Event::GameWinner {
+                game: 1,
+                player: Some(0)
+            })
+        );
+        assert!(combat.log().contains(&Event::Repeated { game: 1 }));
+        assert_eq!(outcome.decks[2], vec![3, 2, 1]);
+    }
+}
@@ -1,4 +1,5 @@
 mod day22;
+mod engine;
 
 pub use day22::part1;
 pub use day22::part2;
@@ -0,0 +1,322 @@
+use std::fmt;
+
+const ABSENT: usize = usize::MAX;
+
+// labels separated by commas or whitespace, or a plain string of single digit labels
+pub fn parse_labels(input: &str) -> Result<Vec<usize>, CircleError> {
+    let input = input.trim();
+    if input.contains(|c: char| c == ',' || c.is_whitespace()) {
+        input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().map_err(|_| CircleError::NotALabel(s.to_string())))
+            .collect()
+    } else {
+        input
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|digit| digit as usize)
+                    .ok_or_else(|| CircleError::NotALabel(c.to_string()))
+            })
+            .collect()
+    }
+}
+
+// what happened in a single move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub current: usize,
+    pub picked: Vec<usize>,
+    pub destination: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircleError {
+    // a move needs the current cup, the picked cups and a destination
+    NotEnoughCups { cups: usize, pick: usize },
+    NothingPicked,
+    Duplicate(usize),
+    // something in the input that isn't a number
+    NotALabel(String),
+}
+
+// Credit to rocurley for the idea to simulate a linked list with a vec of indices
+// https://github.com/rocurley/aoc2020/blob/master/src/day23.rs
+//
+// links[label - lowest] is the label of the cup clockwise of it. Labels don't have to
+// start at 1 or be contiguous, missing labels are skipped when looking for the destination.
+pub struct CupCircle {
+    links: Vec<usize>,
+    lowest: usize,
+    len: usize,
+    current: usize,
+    pick: usize,
+    moves: usize,
+}
+
+impl CupCircle {
+    // cups in clockwise order, the first one being the current cup, and `pick` cups
+    // picked up every move.
+    // links has a slot for every label from the lowest to the highest, so a few
+    // labels that are far apart take as much memory as every label in between
+    pub fn new(labels: &[usize], pick: usize) -> Result<Self, CircleError> {
+        if pick == 0 {
+            return Err(CircleError::NothingPicked);
+        }
+        if labels.len() <= pick + 1 {
+            return Err(CircleError::NotEnoughCups {
+                cups: labels.len(),
+                pick,
+            });
+        }
+        let lowest = *labels.iter().min().unwrap();
+        let highest = *labels.iter().max().unwrap();
+
+        let mut links = vec![ABSENT; highest - lowest + 1];
+        for (idx, &label) in labels.iter().enumerate() {
+            if links[label - lowest] != ABSENT {
+                return Err(CircleError::Duplicate(label));
+            }
+            links[label - lowest] = labels[(idx + 1) % labels.len()];
+        }
+
+        Ok(CupCircle {
+            links,
+            lowest,
+            len: labels.len(),
+            current: labels[0],
+            pick,
+            moves: 0,
+        })
+    }
+
+    pub fn contains(&self, label: usize) -> bool {
+        label >= self.lowest
+            && label - self.lowest < self.links.len()
+            && self.links[label - self.lowest] != ABSENT
+    }
+
+    fn next(&self, label: usize) -> usize {
+        self.links[label - self.lowest]
+    }
+
+    fn set_next(&mut self, label: usize, next: usize) {
+        self.links[label - self.lowest] = next;
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn play(&mut self) -> Move {
+        let picked: Vec<usize> = self.walk(self.current).skip(1).take(self.pick).collect();
+
+        let highest = self.lowest + self.links.len() - 1;
+        let mut destination = self.current;
+        loop {
+            destination = if destination == self.lowest {
+                highest
+            } else {
+                destination - 1
+            };
+            if self.next(destination) != ABSENT && !picked.contains(&destination) {
+                break;
+            }
+        }
+
+        // current -> after the picked cups, destination -> picked -> destination's old next
+        let last_picked = *picked.last().unwrap();
+        self.set_next(self.current, self.next(last_picked));
+        self.set_next(last_picked, self.next(destination));
+        self.set_next(destination, picked[0]);
+
+        let current = self.current;
+        self.current = self.next(current);
+        self.moves += 1;
+        Move {
+            current,
+            picked,
+            destination,
+        }
+    }
+
+    pub fn play_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.play();
+        }
+    }
+
+    // walks clockwise once around the circle, starting at `from`, if there's such a cup
+    pub fn cursor(&self, from: usize) -> Option<Cursor<'_>> {
+        if self.contains(from) {
+            Some(self.walk(from))
+        } else {
+            None
+        }
+    }
+
+    // `from` has to be in the circle
+    fn walk(&self, from: usize) -> Cursor<'_> {
+        Cursor {
+            circle: self,
+            at: from,
+            remaining: self.len,
+        }
+    }
+
+    // the circle as the puzzle shows it, where the current cup moves one position
+    // to the right after every move
+    pub fn snapshot(&self) -> Snapshot {
+        let position = self.moves % self.len;
+        let start = self
+            .walk(self.current)
+            .nth((self.len - position) % self.len)
+            .unwrap();
+        Snapshot {
+            cups: self.walk(start).collect(),
+            current: self.current,
+        }
+    }
+
+    // plays n moves, narrating them like the puzzle's example
+    pub fn play_logged(&mut self, n: usize) -> String {
+        let mut log = String::new();
+        for _ in 0..n {
+            log += &format!("-- move {} --\n", self.moves + 1);
+            log += &format!("cups: {}\n", self.snapshot());
+            let step = self.play();
+            let picked: Vec<String> = step.picked.iter().map(|c| c.to_string()).collect();
+            log += &format!("pick up: {}\n", picked.join(", "));
+            log += &format!("destination: {}\n\n", step.destination);
+        }
+        log += &format!("-- final --\ncups: {}\n", self.snapshot());
+        log
+    }
+}
+
+pub struct Cursor<'a> {
+    circle: &'a CupCircle,
+    at: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Cursor<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let label = self.at;
+        self.at = self.circle.next(label);
+        Some(label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub cups: Vec<usize>,
+    pub current: usize,
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &cup in &self.cups {
+            if cup == self.current {
+                write!(f, "({})", cup)?;
+            } else {
+                write!(f, " {} ", cup)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_labels, CircleError, CupCircle};
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_labels("389\n"), Ok(vec![3, 8, 9]));
+        assert_eq!(parse_labels("10, 2,33"), Ok(vec![10, 2, 33]));
+        assert_eq!(parse_labels("10 2 33"), Ok(vec![10, 2, 33]));
+        assert_eq!(
+            parse_labels("12a"),
+            Err(CircleError::NotALabel("a".to_string()))
+        );
+        assert_eq!(
+            parse_labels("10, 2b"),
+            Err(CircleError::NotALabel("2b".to_string()))
+        );
+    }
+
+    #[test]
+    fn narration() {
+        let mut circle = CupCircle::new(&parse_labels("389125467").unwrap(), 3).unwrap();
+        let log = circle.play_logged(10);
+        let expected_start = concat!(
+            "-- move 1 --\n",
+            "cups: (3) 8  9  1  2  5  4  6  7 \n",
+            "pick up: 8, 9, 1\n",
+            "destination: 2\n\n",
+            "-- move 2 --\n",
+            "cups:  3 (2) 8  9  1  5  4  6  7 \n",
+            "pick up: 8, 9, 1\n",
+            "destination: 7\n\n",
+            "-- move 3 --\n",
+            "cups:  3  2 (5) 4  6  7  8  9  1 \n",
+            "pick up: 4, 6, 7\n",
+            "destination: 3\n",
+        );
+        assert!(log.starts_with(expected_start));
+        assert!(log.ends_with("-- final --\ncups:  5 (8) 3  7  4  1  9  2  6 \n"));
+    }
+
+    #[test]
+    fn labels_and_pick_count() {
+        // labels from 10 to 15 with a gap at 12, picking 2 cups
+        let mut circle = CupCircle::new(&[13, 10, 15, 11, 14], 2).unwrap();
+        let step = circle.play();
+        assert_eq!(step.picked, vec![10, 15]);
+        // 12 doesn't exist, so the destination is 11
+        assert_eq!(step.destination, 11);
+        assert_eq!(
+            circle.cursor(13).unwrap().collect::<Vec<_>>(),
+            vec![13, 11, 10, 15, 14]
+        );
+        assert_eq!(circle.current(), 11);
+
+        // 10 is picked and the lowest label, so it wraps around to 15, also picked
+        let step = circle.play();
+        assert_eq!(step.picked, vec![10, 15]);
+        assert_eq!(step.destination, 14);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            CupCircle::new(&[1, 2, 3], 0).err(),
+            Some(CircleError::NothingPicked)
+        );
+        assert_eq!(
+            CupCircle::new(&[1, 2, 3], 2).err(),
+            Some(CircleError::NotEnoughCups { cups: 3, pick: 2 })
+        );
+        assert_eq!(
+            CupCircle::new(&[1, 2, 3, 2], 1).err(),
+            Some(CircleError::Duplicate(2))
+        );
+
+        let circle = CupCircle::new(&[13, 10, 15, 11, 14], 2).unwrap();
+        assert!(circle.cursor(12).is_none());
+        assert!(circle.cursor(9).is_none());
+        assert!(circle.cursor(16).is_none());
+    }
+}
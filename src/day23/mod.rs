@@ -1,3 +1,4 @@
+mod circle;
 mod day23;
 
 pub use day23::part1;
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
+use aoc2020::image::{self, Frames, BLACK, WHITE};
 use aoc2020::Vector2;
 
 #[derive(Debug, Clone, Copy)]
@@ -127,6 +128,26 @@ pub fn part2(input: &str) -> usize {
     flipped.len()
 }
 
+// the black tiles on every day of the exhibit, all frames big enough for every day
+pub fn frames(input: &str, days: usize) -> Frames {
+    let hexagon_directions = read_directons(input);
+    let mut flipped = flip_from_directions(hexagon_directions);
+
+    let mut all_days = vec![flipped.clone()];
+    for _ in 0..days {
+        flipped = conway(flipped);
+        all_days.push(flipped.clone());
+    }
+
+    let mut frames = Frames::new();
+    if let Some(bounds) = image::hex_bounds(all_days.iter().flatten()) {
+        for day in all_days.iter() {
+            frames.push(image::from_hexes(day.iter(), bounds, BLACK, WHITE));
+        }
+    }
+    frames
+}
+
 mod test {
     #[test]
     fn part1() {
@@ -138,4 +159,25 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part2(input), 3768);
     }
+
+    #[test]
+    fn frames() {
+        let input = include_str!("input");
+        let frames = super::frames(input, 3);
+        assert_eq!(frames.images.len(), 4);
+        let (width, height) = (frames.images[0].width, frames.images[0].height);
+        assert!(frames
+            .images
+            .iter()
+            .all(|image| (image.width, image.height) == (width, height)));
+        // part 1's black tiles on day 0, two pixels each
+        let black = |image: &aoc2020::image::Image| {
+            image
+                .pixels
+                .iter()
+                .filter(|&&p| p == aoc2020::image::BLACK)
+                .count()
+        };
+        assert_eq!(black(&frames.images[0]), 2 * 400);
+    }
 }
@@ -1,22 +1,14 @@
-fn transform(subject: u64, n: usize) -> u64 {
-    let mut value = subject;
-    for _ in 1..n {
-        value *= subject;
-        value %= 20201227;
-    }
-    value
+use aoc2020::number_theory::{discrete_log, mod_pow};
+
+pub const SUBJECT: u64 = 7;
+pub const MODULUS: u64 = 20201227;
+
+pub fn transform(subject: u64, loop_size: u64, modulus: u64) -> u64 {
+    mod_pow(subject as i128, loop_size as u128, modulus as i128) as u64
 }
 
-fn search_loop_size(subject: u64, goal: u64) -> usize {
-    let mut value = subject;
-    for i in 1.. {
-        if value == goal {
-            return i;
-        }
-        value *= subject;
-        value %= 20201227;
-    }
-    unreachable!();
+pub fn search_loop_size(subject: u64, public_key: u64, modulus: u64) -> Option<u64> {
+    discrete_log(subject as i128, public_key as i128, modulus as i128).map(|n| n as u64)
 }
 
 fn parse_input(input: &str) -> (u64, u64) {
@@ -27,11 +19,15 @@ fn parse_input(input: &str) -> (u64, u64) {
     (card_pubkey, door_pubkey)
 }
 
-pub fn part1(input: &str) -> u64 {
+// breaks the handshake by finding the card's loop size from its public key
+pub fn find_encryption_key(input: &str, subject: u64, modulus: u64) -> Option<u64> {
     let (card_pubkey, door_pubkey) = parse_input(input);
-    let card_loopsize = search_loop_size(7, card_pubkey);
-    let encryption_key = transform(door_pubkey, card_loopsize);
-    encryption_key
+    let card_loopsize = search_loop_size(subject, card_pubkey, modulus)?;
+    Some(transform(door_pubkey, card_loopsize, modulus))
+}
+
+pub fn part1(input: &str) -> u64 {
+    find_encryption_key(input, SUBJECT, MODULUS).unwrap()
 }
 
 pub fn part2(_input: &str) -> String {
@@ -45,4 +41,9 @@ mod test {
         let input = include_str!("input");
         assert_eq!(super::part1(input), 10187657);
     }
+
+    #[test]
+    fn example() {
+        assert_eq!(super::part1("5764801\n17807724\n"), 14897079);
+    }
 }
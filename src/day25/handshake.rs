@@ -0,0 +1,143 @@
+use aoc2020::XorShift;
+
+use super::day25::{transform, MODULUS, SUBJECT};
+
+// the public half of a device, what gets transmitted and shows up in the puzzle input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Device {
+    pub loop_size: u64,
+    pub public_key: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handshake {
+    pub card: Device,
+    pub door: Device,
+    pub encryption_key: u64,
+}
+
+impl Handshake {
+    // the two public keys, in the same format as the puzzle input
+    pub fn input(&self) -> String {
+        format!("{}\n{}\n", self.card.public_key, self.door.public_key)
+    }
+}
+
+// there's nothing to compute modulo 0, and everything is 0 modulo 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidModulus(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Protocol {
+    subject: u64,
+    modulus: u64,
+}
+
+impl Default for Protocol {
+    fn default() -> Self {
+        Protocol {
+            subject: SUBJECT,
+            modulus: MODULUS,
+        }
+    }
+}
+
+impl Protocol {
+    pub fn new(subject: u64, modulus: u64) -> Result<Self, InvalidModulus> {
+        if modulus < 2 {
+            return Err(InvalidModulus(modulus));
+        }
+        Ok(Protocol { subject, modulus })
+    }
+
+    pub fn subject(&self) -> u64 {
+        self.subject
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    pub fn device(&self, loop_size: u64) -> Device {
+        Device {
+            loop_size,
+            public_key: transform(self.subject, loop_size, self.modulus),
+        }
+    }
+
+    // what a device computes from its own loop size and the other device's public key
+    pub fn encryption_key(&self, own: &Device, other: &Device) -> u64 {
+        transform(other.public_key, own.loop_size, self.modulus)
+    }
+
+    // both sides end up with subject^(card loop size * door loop size), a
+    // disagreement would be a bug in transform
+    pub fn handshake(&self, card_loop_size: u64, door_loop_size: u64) -> Handshake {
+        let card = self.device(card_loop_size);
+        let door = self.device(door_loop_size);
+        let encryption_key = self.encryption_key(&card, &door);
+        assert_eq!(
+            encryption_key,
+            self.encryption_key(&door, &card),
+            "card and door disagree on the key"
+        );
+        Handshake {
+            card,
+            door,
+            encryption_key,
+        }
+    }
+
+    // handshake with loop sizes in 1..modulus picked by a xorshift generator, the same
+    // seed always gives the same handshake
+    pub fn generate(&self, seed: u64) -> Handshake {
+        let mut loop_sizes = XorShift::new(seed).map(|n| 1 + n % (self.modulus - 1));
+        let card_loop_size = loop_sizes.next().unwrap();
+        let door_loop_size = loop_sizes.next().unwrap();
+        self.handshake(card_loop_size, door_loop_size)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InvalidModulus, Protocol};
+    use crate::day25::day25::find_encryption_key;
+
+    #[test]
+    fn example() {
+        let handshake = Protocol::default().handshake(8, 11);
+        assert_eq!(handshake.card.public_key, 5764801);
+        assert_eq!(handshake.door.public_key, 17807724);
+        assert_eq!(handshake.encryption_key, 14897079);
+    }
+
+    #[test]
+    fn solver() {
+        let protocols = [
+            Protocol::default(),
+            Protocol::new(5, 1_000_000_007).unwrap(),
+            Protocol::new(2, 101 * 103).unwrap(),
+        ];
+        for protocol in protocols.iter() {
+            for seed in 1..=10 {
+                let handshake = protocol.generate(seed);
+                let (card, door) = (handshake.card, handshake.door);
+                assert_eq!(
+                    protocol.encryption_key(&card, &door),
+                    protocol.encryption_key(&door, &card)
+                );
+                let found =
+                    find_encryption_key(&handshake.input(), protocol.subject(), protocol.modulus());
+                assert_eq!(found, Some(handshake.encryption_key));
+            }
+        }
+    }
+
+    #[test]
+    fn bad_modulus() {
+        assert_eq!(Protocol::new(7, 0), Err(InvalidModulus(0)));
+        assert_eq!(Protocol::new(7, 1), Err(InvalidModulus(1)));
+        let protocol = Protocol::new(3, 2).unwrap();
+        assert_eq!(protocol.generate(42).card.loop_size, 1);
+    }
+}
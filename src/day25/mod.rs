@@ -1,4 +1,5 @@
 mod day25;
+mod handshake;
 
 pub use day25::part1;
 pub use day25::part2;
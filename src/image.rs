@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{Grid, Vector2};
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+pub const RED: Rgb = [255, 0, 0];
+
+// plain (ascii) netpbm files shouldn't have lines longer than 70 characters
+const LINE_WIDTH: usize = 70;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    // every pixel becomes a factor x factor square, tiny grids are hard to see otherwise
+    pub fn scale(&self, factor: usize) -> Image {
+        let mut result = Image::new(self.width * factor, self.height * factor, BLACK);
+        for y in 0..result.height {
+            for x in 0..result.width {
+                result.set(x, y, self.get(x / factor, y / factor));
+            }
+        }
+        result
+    }
+
+    // plain ppm, P3
+    pub fn to_ppm(&self) -> String {
+        let header = format!("P3\n{} {}\n255\n", self.width, self.height);
+        let values = self
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.iter().map(|v| v.to_string()));
+        header + &wrap(values)
+    }
+
+    // plain pbm, P1, dark pixels are 1
+    pub fn to_pbm(&self) -> String {
+        let header = format!("P1\n{} {}\n", self.width, self.height);
+        let values = self.pixels.iter().map(|pixel| {
+            let brightness: u32 = pixel.iter().map(|&v| v as u32).sum();
+            if brightness < 384 { "1" } else { "0" }.to_string()
+        });
+        header + &wrap(values)
+    }
+
+    pub fn write_ppm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_ppm())
+    }
+
+    pub fn write_pbm<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_pbm())
+    }
+}
+
+// space separated values, broken into lines that fit LINE_WIDTH
+fn wrap(values: impl Iterator<Item = String>) -> String {
+    let mut result = String::new();
+    let mut line_len = 0;
+    for value in values {
+        if line_len > 0 && line_len + 1 + value.len() > LINE_WIDTH {
+            result.push('\n');
+            line_len = 0;
+        } else if line_len > 0 {
+            result.push(' ');
+            line_len += 1;
+        }
+        line_len += value.len();
+        result += &value;
+    }
+    result.push('\n');
+    result
+}
+
+// colour of every char in a grid, anything else gets the default
+#[derive(Clone, Debug)]
+pub struct Palette {
+    colours: HashMap<char, Rgb>,
+    default: Rgb,
+}
+
+impl Palette {
+    pub fn new(default: Rgb) -> Self {
+        Palette {
+            colours: HashMap::new(),
+            default,
+        }
+    }
+
+    pub fn with(mut self, c: char, colour: Rgb) -> Self {
+        self.colours.insert(c, colour);
+        self
+    }
+
+    pub fn colour(&self, c: char) -> Rgb {
+        *self.colours.get(&c).unwrap_or(&self.default)
+    }
+}
+
+// '#' black on white, what most days use
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::new(WHITE).with('#', BLACK)
+    }
+}
+
+pub fn from_grid(grid: &Grid, palette: &Palette) -> Image {
+    let mut image = Image::new(grid.cols as usize, grid.rows as usize, palette.default);
+    for (idx, &c) in grid.data.iter().enumerate() {
+        image.pixels[idx] = palette.colour(c);
+    }
+    image
+}
+
+// the smallest rectangle holding every position, inclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Vector2,
+    pub max: Vector2,
+}
+
+impl Bounds {
+    pub fn of<'a>(positions: impl IntoIterator<Item = &'a Vector2>) -> Option<Bounds> {
+        let mut positions = positions.into_iter();
+        let first = *positions.next()?;
+        Some(positions.fold(Bounds::new(first, first), |bounds, pos| {
+            bounds.merge(&Bounds::new(*pos, *pos))
+        }))
+    }
+
+    pub fn new(min: Vector2, max: Vector2) -> Self {
+        Bounds { min, max }
+    }
+
+    pub fn merge(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            min: Vector2::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Vector2::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    pub fn contains(&self, pos: &Vector2) -> bool {
+        pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
+    }
+}
+
+// a sparse set of positions, like day17's active cubes, drawn inside `bounds` so
+// every frame of a simulation can share the same size. Positions outside are left out
+pub fn from_points<'a>(
+    points: impl IntoIterator<Item = &'a Vector2>,
+    bounds: Bounds,
+    on: Rgb,
+    off: Rgb,
+) -> Image {
+    let mut image = Image::new(bounds.width(), bounds.height(), off);
+    for pos in points.into_iter().filter(|pos| bounds.contains(pos)) {
+        let (x, y) = (pos.x - bounds.min.x, pos.y - bounds.min.y);
+        image.set(x as usize, y as usize, on);
+    }
+    image
+}
+
+// hexes in day24's axial coordinates, where x goes east and y goes north east.
+// Each hex is two pixels wide and every row north is shifted half a hex east
+fn hex_pixel(pos: &Vector2) -> Vector2 {
+    Vector2::new(2 * pos.x + pos.y, -pos.y)
+}
+
+// the pixel bounds `from_hexes` needs for these hexes
+pub fn hex_bounds<'a>(hexes: impl IntoIterator<Item = &'a Vector2>) -> Option<Bounds> {
+    let pixels: Vec<Vector2> = hexes.into_iter().map(hex_pixel).collect();
+    let bounds = Bounds::of(pixels.iter())?;
+    Some(Bounds::new(bounds.min, bounds.max + Vector2::new(1, 0)))
+}
+
+pub fn from_hexes<'a>(
+    hexes: impl IntoIterator<Item = &'a Vector2>,
+    bounds: Bounds,
+    on: Rgb,
+    off: Rgb,
+) -> Image {
+    let pixels: Vec<Vector2> = hexes
+        .into_iter()
+        .map(hex_pixel)
+        .flat_map(|pixel| vec![pixel, pixel + Vector2::new(1, 0)])
+        .collect();
+    from_points(pixels.iter(), bounds, on, off)
+}
+
+// paints some cells of an image drawn from a grid, like the sea monsters in day20.
+// Cells outside the image are left out
+pub fn highlight<'a>(image: &mut Image, cells: impl IntoIterator<Item = &'a Vector2>, colour: Rgb) {
+    let bounds = Bounds::new(
+        Vector2::new(0, 0),
+        Vector2::new(image.width as i64 - 1, image.height as i64 - 1),
+    );
+    for pos in cells.into_iter().filter(|pos| bounds.contains(pos)) {
+        image.set(pos.x as usize, pos.y as usize, colour);
+    }
+}
+
+// the steps of a simulation, written as one numbered file per frame
+#[derive(Clone, Debug, Default)]
+pub struct Frames {
+    pub images: Vec<Image>,
+}
+
+impl Frames {
+    pub fn new() -> Self {
+        Frames { images: Vec::new() }
+    }
+
+    pub fn push(&mut self, image: Image) {
+        self.images.push(image);
+    }
+
+    // dir/name_0000.ppm, dir/name_0001.ppm, ...
+    pub fn write<P: AsRef<Path>>(&self, dir: P, name: &str) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        for (idx, image) in self.images.iter().enumerate() {
+            let path = dir.as_ref().join(format!("{}_{:04}.ppm", name, idx));
+            image.write_ppm(path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_grid, from_hexes, from_points, hex_bounds, highlight, wrap};
+    use super::{Bounds, Frames, Palette, BLACK, RED, WHITE};
+    use crate::{parse_grid, Vector2};
+
+    #[test]
+    fn ppm_and_pbm() {
+        let grid = parse_grid("#.\n.O");
+        let palette = Palette::default().with('O', RED);
+        let image = from_grid(&grid, &palette);
+        assert_eq!(
+            image.to_ppm(),
+            "P3\n2 2\n255\n0 0 0 255 255 255 255 255 255 255 0 0\n"
+        );
+        // red is dark enough to be ink
+        assert_eq!(image.to_pbm(), "P1\n2 2\n1 0 0 1\n");
+
+        let scaled = image.scale(2);
+        assert_eq!((scaled.width, scaled.height), (4, 4));
+        assert_eq!(scaled.get(1, 1), BLACK);
+        assert_eq!(scaled.get(2, 1), WHITE);
+    }
+
+    #[test]
+    fn long_lines() {
+        let wrapped = wrap((0..100).map(|_| "255".to_string()));
+        assert!(wrapped.lines().all(|line| line.len() <= 70));
+        assert_eq!(wrapped.split_whitespace().count(), 100);
+    }
+
+    #[test]
+    fn points() {
+        let points = [Vector2::new(-1, 2), Vector2::new(1, 3)];
+        let bounds = Bounds::of(points.iter()).unwrap();
+        assert_eq!(bounds, Bounds::new(Vector2::new(-1, 2), Vector2::new(1, 3)));
+
+        let mut image = from_points(points.iter(), bounds, BLACK, WHITE);
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(image.get(0, 0), BLACK);
+        assert_eq!(image.get(2, 1), BLACK);
+        assert_eq!(image.get(1, 0), WHITE);
+
+        highlight(&mut image, &[Vector2::new(1, 0)], RED);
+        assert_eq!(image.get(1, 0), RED);
+        // nothing to paint outside the image
+        let outside = [Vector2::new(-1, 0), Vector2::new(3, 1), Vector2::new(0, 2)];
+        let before = image.clone();
+        highlight(&mut image, &outside, RED);
+        assert_eq!(image, before);
+    }
+
+    #[test]
+    fn hexes() {
+        // the origin, its east neighbour and its north east neighbour
+        let hexes = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)];
+        let bounds = hex_bounds(hexes.iter()).unwrap();
+        let image = from_hexes(hexes.iter(), bounds, BLACK, WHITE);
+        let rows: Vec<String> = image
+            .pixels
+            .chunks(image.width)
+            .map(|row| {
+                row.iter()
+                    .map(|&p| if p == BLACK { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, vec![".##.", "####"]);
+    }
+
+    #[test]
+    fn frames() {
+        let mut frames = Frames::new();
+        let grid = parse_grid("#");
+        frames.push(from_grid(&grid, &Palette::default()));
+        frames.push(from_grid(&grid, &Palette::new(RED)));
+
+        let dir = std::env::temp_dir().join(format!("aoc2020_frames_{}", std::process::id()));
+        frames.write(&dir, "step").unwrap();
+        let second = std::fs::read_to_string(dir.join("step_0001.ppm")).unwrap();
+        assert_eq!(second, "P3\n1 1\n255\n255 0 0\n");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
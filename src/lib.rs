@@ -2,6 +2,11 @@ use std::collections::BTreeMap;
 use std::ops::{Add, AddAssign, Mul, MulAssign};
 use std::str::FromStr;
 
+pub mod image;
+pub mod matching;
+pub mod number_theory;
+pub mod pattern;
+
 #[derive(Clone, Copy)]
 pub enum Direction {
     Up,
@@ -122,6 +127,76 @@ impl Grid {
     }
 }
 
+// one of the 8 ways to place a grid: mirrored left to right or not, then turned
+// clockwise 0 to 3 times
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Orientation {
+    pub flipped: bool,
+    pub rotations: u8,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(false, 0),
+        Orientation::new(false, 1),
+        Orientation::new(false, 2),
+        Orientation::new(false, 3),
+        Orientation::new(true, 0),
+        Orientation::new(true, 1),
+        Orientation::new(true, 2),
+        Orientation::new(true, 3),
+    ];
+
+    pub const fn new(flipped: bool, rotations: u8) -> Self {
+        Orientation {
+            flipped,
+            rotations: rotations % 4,
+        }
+    }
+
+    // position in Orientation::ALL
+    pub fn index(self) -> usize {
+        self.flipped as usize * 4 + self.rotations as usize
+    }
+
+    // (cols, rows) of a cols x rows grid after orienting it
+    pub fn size(self, cols: i64, rows: i64) -> (i64, i64) {
+        if self.rotations & 1 == 0 {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        }
+    }
+
+    // where `pos` of the oriented grid comes from in the original cols x rows grid
+    pub fn source(self, pos: Vector2, cols: i64, rows: i64) -> Vector2 {
+        let (mut x, mut y) = (pos.x, pos.y);
+        let (mut oriented_cols, mut oriented_rows) = self.size(cols, rows);
+        for _ in 0..self.rotations {
+            // undo one clockwise turn
+            let previous = (y, oriented_cols - 1 - x);
+            x = previous.0;
+            y = previous.1;
+            std::mem::swap(&mut oriented_cols, &mut oriented_rows);
+        }
+        if self.flipped {
+            x = cols - 1 - x;
+        }
+        Vector2::new(x, y)
+    }
+
+    pub fn apply(self, grid: &Grid) -> Grid {
+        let (cols, rows) = self.size(grid.cols, grid.rows);
+        let mut data = Vec::with_capacity(grid.data.len());
+        for y in 0..rows {
+            for x in 0..cols {
+                data.push(grid.get(&self.source(Vector2::new(x, y), grid.cols, grid.rows)));
+            }
+        }
+        Grid { data, cols, rows }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Vector2 {
     pub y: i64, // sort by y before x
@@ -212,6 +287,34 @@ impl CharSet {
         }
     }
 }
+#[cfg(test)]
+mod orientation_test {
+    use super::{parse_grid, Orientation};
+
+    #[test]
+    fn orientations() {
+        let grid = parse_grid("ab\ncd\nef");
+        let oriented = |flipped, rotations| {
+            let result = Orientation::new(flipped, rotations).apply(&grid);
+            (result.cols, result.data.into_iter().collect::<String>())
+        };
+        assert_eq!(oriented(false, 0), (2, "abcdef".to_string()));
+        assert_eq!(oriented(false, 1), (3, "ecafdb".to_string()));
+        assert_eq!(oriented(false, 2), (2, "fedcba".to_string()));
+        assert_eq!(oriented(true, 0), (2, "badcfe".to_string()));
+        assert_eq!(oriented(true, 1), (3, "fdbeca".to_string()));
+
+        let all: Vec<String> = Orientation::ALL
+            .iter()
+            .map(|o| o.apply(&grid).data.into_iter().collect())
+            .collect();
+        for (idx, a) in all.iter().enumerate() {
+            assert_eq!(Orientation::ALL[idx].index(), idx);
+            assert!(all[idx + 1..].iter().all(|b| a != b));
+        }
+    }
+}
+
 #[cfg(test)]
 mod charset_test {
     use super::CharSet;
@@ -237,3 +340,26 @@ where
     }
     result
 }
+
+// xorshift64, a tiny deterministic generator for made up inputs: the same seed
+// always gives the same numbers
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    // a state of 0 would only ever give zeros
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed.max(1))
+    }
+}
+
+impl Iterator for XorShift {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        Some(self.0)
+    }
+}
@@ -1,5 +1,3 @@
-#![feature(hash_drain_filter)]
-#![feature(drain_filter)]
 #![feature(str_split_once)]
 #![allow(dead_code)]
 mod day01;
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+// assigning every key a different value out of its own candidates, like day16's
+// fields to ticket positions or day21's allergens to ingredients
+
+// keys that can only take one of `values` between them, and there are fewer values
+// than keys, so at least one of them is left without a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict<K, V> {
+    pub keys: Vec<K>,
+    pub values: Vec<V>,
+}
+
+// keys and values replaced by their index, candidates[key] holds value indices
+struct Problem<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    candidates: Vec<Vec<usize>>,
+}
+
+impl<K: Ord + Clone, V: Ord + Clone> Problem<K, V> {
+    fn new<C>(candidates: impl IntoIterator<Item = (K, C)>) -> Self
+    where
+        C: IntoIterator<Item = V>,
+    {
+        let by_key: BTreeMap<K, BTreeSet<V>> = candidates
+            .into_iter()
+            .map(|(key, options)| (key, options.into_iter().collect()))
+            .collect();
+        let values: Vec<V> = by_key
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<BTreeSet<V>>()
+            .into_iter()
+            .collect();
+        let candidates = by_key
+            .values()
+            .map(|options| {
+                options
+                    .iter()
+                    .map(|value| values.binary_search(value).unwrap())
+                    .collect()
+            })
+            .collect();
+
+        Problem {
+            keys: by_key.into_keys().collect(),
+            values,
+            candidates,
+        }
+    }
+
+    fn solution(&self, assigned: &[usize]) -> BTreeMap<K, V> {
+        assigned
+            .iter()
+            .enumerate()
+            .map(|(key, &value)| (self.keys[key].clone(), self.values[value].clone()))
+            .collect()
+    }
+
+    fn conflict(&self, keys: Vec<usize>, values: Vec<usize>) -> Conflict<K, V> {
+        Conflict {
+            keys: keys.into_iter().map(|k| self.keys[k].clone()).collect(),
+            values: values.into_iter().map(|v| self.values[v].clone()).collect(),
+        }
+    }
+}
+
+// a value for every key, or the keys that can't all get one
+pub fn matching<K, V, C>(
+    candidates: impl IntoIterator<Item = (K, C)>,
+) -> Result<BTreeMap<K, V>, Conflict<K, V>>
+where
+    K: Ord + Clone,
+    V: Ord + Clone,
+    C: IntoIterator<Item = V>,
+{
+    let problem = Problem::new(candidates);
+    match max_matching(&problem.candidates, problem.values.len()) {
+        Ok(assigned) => Ok(problem.solution(&assigned)),
+        Err((keys, values)) => Err(problem.conflict(keys, values)),
+    }
+}
+
+// every possible assignment, in order, or the keys that can't all get a value
+pub fn all_matchings<K, V, C>(
+    candidates: impl IntoIterator<Item = (K, C)>,
+) -> Result<Vec<BTreeMap<K, V>>, Conflict<K, V>>
+where
+    K: Ord + Clone,
+    V: Ord + Clone,
+    C: IntoIterator<Item = V>,
+{
+    let problem = Problem::new(candidates);
+    if let Err((keys, values)) = max_matching(&problem.candidates, problem.values.len()) {
+        return Err(problem.conflict(keys, values));
+    }
+
+    let mut solutions = Vec::new();
+    search(
+        problem.candidates.clone(),
+        problem.values.len(),
+        &mut solutions,
+    );
+    Ok(solutions
+        .iter()
+        .map(|assigned| problem.solution(assigned))
+        .collect())
+}
+
+// a key with a single candidate left takes it, so no other key can,
+// repeated until nothing changes. false if some key runs out of candidates
+fn eliminate(candidates: &mut [Vec<usize>]) -> bool {
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for key in 0..candidates.len() {
+            if candidates[key].len() != 1 {
+                continue;
+            }
+            let value = candidates[key][0];
+            for (other, options) in candidates.iter_mut().enumerate() {
+                if other != key && options.contains(&value) {
+                    options.retain(|&v| v != value);
+                    if options.is_empty() {
+                        return false;
+                    }
+                    changed = true;
+                }
+            }
+        }
+    }
+    true
+}
+
+// eliminate, then try every candidate of the key with the fewest left
+fn search(mut candidates: Vec<Vec<usize>>, values: usize, solutions: &mut Vec<Vec<usize>>) {
+    if !eliminate(&mut candidates) || max_matching(&candidates, values).is_err() {
+        return;
+    }
+    let undecided = (0..candidates.len())
+        .filter(|&key| candidates[key].len() > 1)
+        .min_by_key(|&key| candidates[key].len());
+
+    match undecided {
+        None => solutions.push(candidates.iter().map(|options| options[0]).collect()),
+        Some(key) => {
+            for &value in candidates[key].iter() {
+                let mut next = candidates.clone();
+                next[key] = vec![value];
+                search(next, values, solutions);
+            }
+        }
+    }
+}
+
+// maximum bipartite matching by augmenting paths, the value of every key.
+// if a key can't be matched, every value its search visited is already taken, and
+// their owners can't move anywhere else either: those keys and values are the conflict
+fn max_matching(
+    candidates: &[Vec<usize>],
+    values: usize,
+) -> Result<Vec<usize>, (Vec<usize>, Vec<usize>)> {
+    let mut owner: Vec<Option<usize>> = vec![None; values];
+    for key in 0..candidates.len() {
+        let mut seen = vec![false; values];
+        if !augment(key, candidates, &mut owner, &mut seen) {
+            let taken: Vec<usize> = (0..values).filter(|&v| seen[v]).collect();
+            let mut keys: Vec<usize> = taken.iter().map(|&v| owner[v].unwrap()).collect();
+            keys.push(key);
+            keys.sort_unstable();
+            return Err((keys, taken));
+        }
+    }
+
+    let mut assigned = vec![0; candidates.len()];
+    for (value, key) in owner.iter().enumerate() {
+        if let Some(key) = key {
+            assigned[*key] = value;
+        }
+    }
+    Ok(assigned)
+}
+
+fn augment(
+    key: usize,
+    candidates: &[Vec<usize>],
+    owner: &mut [Option<usize>],
+    seen: &mut [bool],
+) -> bool {
+    for &value in candidates[key].iter() {
+        if seen[value] {
+            continue;
+        }
+        seen[value] = true;
+        let free = match owner[value] {
+            None => true,
+            Some(other) => augment(other, candidates, owner, seen),
+        };
+        if free {
+            owner[value] = Some(key);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::{all_matchings, matching, Conflict};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn single_solution() {
+        // c only fits 2, which leaves 1 for a and 3 for b
+        let candidates = vec![("a", vec![1, 2]), ("b", vec![2, 3]), ("c", vec![2])];
+        let expected: BTreeMap<_, _> = vec![("a", 1), ("b", 3), ("c", 2)].into_iter().collect();
+        assert_eq!(matching(candidates.clone()), Ok(expected.clone()));
+        assert_eq!(all_matchings(candidates), Ok(vec![expected]));
+    }
+
+    #[test]
+    fn every_solution() {
+        let candidates = vec![("a", vec![1, 2]), ("b", vec![1, 2]), ("c", vec![3, 4])];
+        let solutions = all_matchings(candidates).unwrap();
+        assert_eq!(solutions.len(), 4);
+        assert_eq!(solutions[0].values().collect::<Vec<_>>(), vec![&1, &2, &3]);
+        assert_eq!(solutions[3].values().collect::<Vec<_>>(), vec![&2, &1, &4]);
+    }
+
+    #[test]
+    fn conflict() {
+        // a, b and c share two values between them, d is fine
+        let candidates = vec![
+            ("a", vec![1, 2]),
+            ("b", vec![2]),
+            ("c", vec![1, 2]),
+            ("d", vec![2, 3]),
+        ];
+        let expected = Conflict {
+            keys: vec!["a", "b", "c"],
+            values: vec![1, 2],
+        };
+        assert_eq!(matching(candidates.clone()), Err(expected.clone()));
+        assert_eq!(all_matchings(candidates), Err(expected));
+
+        let empty = vec![("a", vec![]), ("b", vec![1])];
+        assert_eq!(
+            matching(empty),
+            Err(Conflict {
+                keys: vec!["a"],
+                values: vec![]
+            })
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+
+// https://rosettacode.org/wiki/Chinese_remainder_theorem#Rust
+// returns (gcd, x, y) such that a * x + b * y = gcd
+pub fn egcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if a == 0 {
+        (b, 0, 1)
+    } else {
+        let (g, x, y) = egcd(b % a, a);
+        (g, y - (b / a) * x, x)
+    }
+}
+
+pub fn gcd(a: i128, b: i128) -> i128 {
+    egcd(a, b).0.abs()
+}
+
+// 0 if either is 0, None if the result doesn't fit
+pub fn lcm(a: i128, b: i128) -> Option<i128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+pub fn mod_inv(x: i128, n: i128) -> Option<i128> {
+    let (g, x, _) = egcd(x.rem_euclid(n), n);
+    if g == 1 {
+        Some(x.rem_euclid(n))
+    } else {
+        None
+    }
+}
+
+// a * b % m without overflowing, even when a * b doesn't fit in an i128
+pub fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    let (mut a, mut b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    // double and add, every intermediate value stays below 2 * m
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+// (a + b) % m for a, b already in 0..m
+fn add_mod(a: i128, b: i128, m: i128) -> i128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CrtError {
+    // congruence at this index contradicts the ones before it
+    Inconsistent(usize),
+    // merging the congruence at this index makes the combined modulus too big
+    Overflow(usize),
+    // the modulus at this index isn't positive
+    Modulus(usize),
+}
+
+// Solves x = residue (mod modulus) for every (residue, modulus) pair, merging them
+// two at a time so moduli don't need to be coprime.
+// Returns (x, lcm of all moduli), x being the smallest non negative solution.
+pub fn chinese_remainder(congruences: &[(i128, i128)]) -> Result<(i128, i128), CrtError> {
+    let mut x = 0;
+    let mut lcm = 1;
+    for (idx, &(residue, modulus)) in congruences.iter().enumerate() {
+        if modulus <= 0 {
+            return Err(CrtError::Modulus(idx));
+        }
+        let residue = residue.rem_euclid(modulus);
+
+        // x + lcm * t = residue (mod modulus)
+        // => lcm * t = residue - x (mod modulus), which is only solvable if
+        // gcd(lcm, modulus) divides residue - x
+        let g = gcd(lcm, modulus);
+        let diff = residue - x;
+        if diff % g != 0 {
+            return Err(CrtError::Inconsistent(idx));
+        }
+        let reduced = modulus / g;
+        let next_lcm = (lcm / g)
+            .checked_mul(modulus)
+            .ok_or(CrtError::Overflow(idx))?;
+        let t = mul_mod(diff / g, mod_inv(lcm / g, reduced).unwrap(), reduced);
+        x = add_mod(x, mul_mod(lcm, t, next_lcm), next_lcm);
+        lcm = next_lcm;
+    }
+    Ok((x, lcm))
+}
+
+pub fn mod_pow(base: i128, mut exp: u128, m: i128) -> i128 {
+    let mut base = base.rem_euclid(m);
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+const WITNESSES: [i128; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+// miller-rabin with the first 13 primes as witnesses, exact below 3.3 * 10^24
+// and very unlikely to be wrong above that
+pub fn is_prime(n: i128) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in WITNESSES.iter() {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+    let mut d = n - 1;
+    let mut s = 0;
+    while d & 1 == 0 {
+        d >>= 1;
+        s += 1;
+    }
+    WITNESSES.iter().all(|&a| {
+        let mut x = mod_pow(a, d as u128, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+// a factor of a composite n with no small prime factors, with pollard's rho
+fn find_factor(n: i128) -> i128 {
+    let mut c = 1;
+    loop {
+        let f = |x| add_mod(mul_mod(x, x, n), c, n);
+        let (mut x, mut y, mut d) = (2, 2, 1);
+        while d == 1 {
+            x = f(x);
+            y = f(f(y));
+            d = gcd(x - y, n);
+        }
+        // the cycle closed without finding anything, try another polynomial
+        if d != n {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+// prime factors of n with their exponents, smallest first. Small factors are
+// found by trial division, the rest with pollard's rho
+pub fn factorize(mut n: i128) -> Vec<(i128, u32)> {
+    let mut primes = Vec::new();
+    let mut p = 2;
+    while p < 1000 && p * p <= n {
+        while n % p == 0 {
+            n /= p;
+            primes.push(p);
+        }
+        p += 1;
+    }
+
+    let mut rest = if n > 1 { vec![n] } else { Vec::new() };
+    while let Some(n) = rest.pop() {
+        if is_prime(n) {
+            primes.push(n);
+        } else {
+            let d = find_factor(n);
+            rest.push(d);
+            rest.push(n / d);
+        }
+    }
+
+    primes.sort_unstable();
+    let mut factors: Vec<(i128, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exp)) if *last == p => *exp += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+// euler's totient, the amount of numbers in 1..=n coprime to n
+pub fn totient(n: i128) -> i128 {
+    factorize(n)
+        .into_iter()
+        .fold(n, |phi, (p, _)| phi / p * (p - 1))
+}
+
+// smallest k > 0 with base^k = 1 (mod m), base must be coprime to m
+pub fn multiplicative_order(base: i128, m: i128) -> i128 {
+    let mut order = totient(m);
+    for (p, _) in factorize(order) {
+        while order % p == 0 && mod_pow(base, (order / p) as u128, m) == 1 % m {
+            order /= p;
+        }
+    }
+    order
+}
+
+// baby-step giant-step: smallest x in 0..order with base^x = target (mod m),
+// where order is a bound on the multiplicative order of base
+pub fn baby_step_giant_step(base: i128, target: i128, order: i128, m: i128) -> Option<i128> {
+    let steps = (order as f64).sqrt().ceil() as i128 + 1;
+
+    // baby steps, base^j for j in 0..steps, keeping the smallest j for each value
+    let mut table = HashMap::new();
+    let mut value = 1 % m;
+    for j in 0..steps {
+        table.entry(value).or_insert(j);
+        value = mul_mod(value, base, m);
+    }
+
+    // giant steps, target * base^(-steps * i)
+    let giant = mod_pow(mod_inv(base, m)?, steps as u128, m);
+    let mut value = target.rem_euclid(m);
+    for i in 0..steps {
+        if let Some(j) = table.get(&value) {
+            let x = i * steps + j;
+            if x < order {
+                return Some(x);
+            }
+        }
+        value = mul_mod(value, giant, m);
+    }
+    None
+}
+
+// Discrete logarithm: smallest x >= 0 with base^x = target (mod m), None if there's
+// no such x.
+// While base shares a factor g with m, base^x = target can only hold for x > 0 if g
+// divides target, so both sides and m are divided by g and x goes up by one. That
+// leaves coef * base^y = target with base coprime to m, solved with pohlig-hellman.
+// It takes as long as factoring m and the order of base, which pollard's rho does
+// quickly unless they have two huge prime factors.
+pub fn discrete_log(base: i128, target: i128, mut m: i128) -> Option<i128> {
+    let mut target = target.rem_euclid(m);
+    let mut coef = 1 % m;
+    let mut steps = 0;
+    loop {
+        let g = gcd(base, m);
+        if g == 1 {
+            break;
+        }
+        // coef is base^steps in the reduced modulus
+        if coef == target {
+            return Some(steps);
+        }
+        if target % g != 0 {
+            return None;
+        }
+        target /= g;
+        m /= g;
+        coef = mul_mod(coef, base / g, m);
+        steps += 1;
+    }
+    let target = mul_mod(target, mod_inv(coef, m)?, m);
+    coprime_log(base, target, m).map(|y| steps + y)
+}
+
+// pohlig-hellman, for base coprime to m.
+// base generates a cyclic group whose order splits into prime powers q^e, the log is
+// found in each of those small subgroups one base q digit at a time with
+// baby-step giant-step, and the pieces are joined back with the chinese remainder.
+fn coprime_log(base: i128, target: i128, m: i128) -> Option<i128> {
+    let order = multiplicative_order(base, m);
+
+    let mut congruences = Vec::new();
+    for (q, e) in factorize(order) {
+        let qe = q.pow(e);
+        // both live in the subgroup of order q^e
+        let g = mod_pow(base, (order / qe) as u128, m);
+        let h = mod_pow(target, (order / qe) as u128, m);
+        // generator of the subgroup of order q
+        let gamma = mod_pow(g, (qe / q) as u128, m);
+        let g_inv = mod_inv(g, m)?;
+
+        let mut x = 0;
+        let mut q_k = 1;
+        for k in 0..e {
+            // strip the digits found so far and keep only digit k
+            let stripped = mul_mod(mod_pow(g_inv, x as u128, m), h, m);
+            let h_k = mod_pow(stripped, q.pow(e - 1 - k) as u128, m);
+            let digit = baby_step_giant_step(gamma, h_k, q, m)?;
+            x += digit * q_k;
+            q_k *= q;
+        }
+        congruences.push((x, qe));
+    }
+
+    let (x, _) = chinese_remainder(&congruences).ok()?;
+    // target might not be in the group generated by base at all
+    if mod_pow(base, x as u128, m) == target.rem_euclid(m) {
+        Some(x)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chinese_remainder, discrete_log, factorize, is_prime, lcm, mod_inv, mod_pow};
+    use super::{mul_mod, CrtError};
+
+    #[test]
+    fn inverse() {
+        assert_eq!(mod_inv(3, 7), Some(5));
+        assert_eq!(mod_inv(-3, 7), Some(2));
+        assert_eq!(mod_inv(4, 8), None);
+    }
+
+    #[test]
+    fn big_multiplication() {
+        let m = i128::MAX - 1;
+        assert_eq!(mul_mod(m - 1, m - 1, m), 1);
+        assert_eq!(mul_mod(1 << 100, 1 << 100, i128::MAX), 1 << 73);
+    }
+
+    #[test]
+    fn coprime() {
+        assert_eq!(chinese_remainder(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+    }
+
+    #[test]
+    fn not_coprime() {
+        assert_eq!(chinese_remainder(&[(2, 4), (4, 6)]), Ok((10, 12)));
+        assert_eq!(
+            chinese_remainder(&[(1, 3), (2, 4), (3, 6)]),
+            Err(CrtError::Inconsistent(2))
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let big = (1 << 100) + 1;
+        assert_eq!(
+            chinese_remainder(&[(0, big), (0, big - 2)]),
+            Err(CrtError::Overflow(1))
+        );
+    }
+
+    #[test]
+    fn multiple() {
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 0), Some(0));
+        assert_eq!(lcm(5, 0), Some(0));
+        assert_eq!(lcm(1 << 100, (1 << 100) + 1), None);
+    }
+
+    #[test]
+    fn bad_modulus() {
+        assert_eq!(
+            chinese_remainder(&[(1, 3), (0, 0)]),
+            Err(CrtError::Modulus(1))
+        );
+        assert_eq!(chinese_remainder(&[(1, -5)]), Err(CrtError::Modulus(0)));
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(mod_pow(7, 8, 20201227), 5764801);
+        assert_eq!(mod_pow(3, 0, 1), 0);
+        assert_eq!(mod_pow(2, 127, i128::MAX), 1);
+    }
+
+    #[test]
+    fn logarithm() {
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(discrete_log(3, 13, 17), Some(4));
+
+        let m = 1_000_000_007;
+        let target = mod_pow(5, 123_456_789, m);
+        assert_eq!(discrete_log(5, target, m), Some(123_456_789));
+    }
+
+    #[test]
+    fn logarithm_composite() {
+        // 2 has order 4 mod 15: 1, 2, 4, 8
+        assert_eq!(discrete_log(2, 8, 15), Some(3));
+        assert_eq!(discrete_log(2, 7, 15), None);
+    }
+
+    #[test]
+    fn logarithm_not_coprime() {
+        assert_eq!(discrete_log(2, 0, 8), Some(3));
+        assert_eq!(discrete_log(3, 1, 15), Some(0));
+        assert_eq!(discrete_log(3, 9, 15), Some(2));
+        assert_eq!(discrete_log(3, 2, 15), None);
+        // 6, 16, 16, ...
+        assert_eq!(discrete_log(6, 16, 20), Some(2));
+        assert_eq!(discrete_log(6, 6, 20), Some(1));
+        for x in 0..40 {
+            let target = mod_pow(12, x, 2 * 2 * 3 * 3 * 3 * 7 * 11);
+            let found = discrete_log(12, target, 2 * 2 * 3 * 3 * 3 * 7 * 11).unwrap();
+            assert!(found <= x as i128);
+            assert_eq!(
+                mod_pow(12, found as u128, 2 * 2 * 3 * 3 * 3 * 7 * 11),
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn factors() {
+        assert!(factorize(1).is_empty());
+        assert_eq!(factorize(360), vec![(2, 3), (3, 2), (5, 1)]);
+        let (p, q) = (1_000_000_007, 998_244_353);
+        assert!(is_prime(p) && is_prime(q) && !is_prime(p * q));
+        assert_eq!(factorize(p * q * q), vec![(q, 2), (p, 1)]);
+        assert_eq!(factorize(p * p * p), vec![(p, 3)]);
+    }
+
+    #[test]
+    fn logarithm_large_modulus() {
+        // a semiprime with two 30 bit factors, too big for trial division to be quick
+        let m = 1_000_000_007 * 998_244_353;
+        let target = mod_pow(3, 987_654_321_012, m);
+        let x = discrete_log(3, target, m).unwrap();
+        assert_eq!(mod_pow(3, x as u128, m), target);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{Grid, Orientation, Vector2};
+
+// an ascii picture to look for in a grid, cells with the wildcard match anything
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub grid: Grid,
+    pub wildcard: char,
+}
+
+// where a pattern was found: the pattern's orientation, the top left corner of
+// the oriented pattern in the grid and every cell it matched
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub orientation: Orientation,
+    pub position: Vector2,
+    pub cells: Vec<Vector2>,
+}
+
+impl Pattern {
+    // shorter lines are padded with wildcards up to the longest one
+    pub fn parse(text: &str, wildcard: char) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let cols = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut data = Vec::with_capacity(cols * lines.len());
+        for line in lines.iter() {
+            let len = line.chars().count();
+            data.extend(line.chars());
+            data.extend(std::iter::repeat_n(wildcard, cols - len));
+        }
+        Pattern {
+            grid: Grid {
+                data,
+                cols: cols as i64,
+                rows: lines.len() as i64,
+            },
+            wildcard,
+        }
+    }
+
+    // the cells that have to match, relative to the top left corner
+    fn cells(&self, grid: &Grid) -> Vec<(Vector2, char)> {
+        let mut cells = Vec::new();
+        for y in 0..grid.rows {
+            for x in 0..grid.cols {
+                let pos = Vector2::new(x, y);
+                let c = grid.get(&pos);
+                if c != self.wildcard {
+                    cells.push((pos, c));
+                }
+            }
+        }
+        cells
+    }
+
+    // every match in every orientation, matches are allowed to overlap.
+    // orientations where the pattern looks the same as in an earlier one are skipped,
+    // so a symmetric pattern isn't found twice in the same place
+    pub fn find(&self, grid: &Grid) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut searched: Vec<Vec<(Vector2, char)>> = Vec::new();
+        for &orientation in Orientation::ALL.iter() {
+            let oriented = orientation.apply(&self.grid);
+            let cells = self.cells(&oriented);
+            if searched.contains(&cells) {
+                continue;
+            }
+            if oriented.cols <= grid.cols && oriented.rows <= grid.rows {
+                for y in 0..=grid.rows - oriented.rows {
+                    for x in 0..=grid.cols - oriented.cols {
+                        let position = Vector2::new(x, y);
+                        if cells
+                            .iter()
+                            .all(|&(pos, c)| grid.get(&(pos + position)) == c)
+                        {
+                            matches.push(Match {
+                                orientation,
+                                position,
+                                cells: cells.iter().map(|&(pos, _)| pos + position).collect(),
+                            });
+                        }
+                    }
+                }
+            }
+            searched.push(cells);
+        }
+        matches
+    }
+}
+
+// a copy of grid with every matched cell replaced by `with`
+pub fn mark(grid: &Grid, matches: &[Match], with: char) -> Grid {
+    let mut result = grid.clone();
+    for pos in matches.iter().flat_map(|m| m.cells.iter()) {
+        result.replace(pos, with);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{mark, Pattern};
+    use crate::{parse_grid, Orientation, Vector2};
+
+    #[test]
+    fn parse() {
+        let pattern = Pattern::parse("#.\n#?#\n", '?');
+        assert_eq!((pattern.grid.cols, pattern.grid.rows), (3, 2));
+        assert_eq!(pattern.grid.data, vec!['#', '.', '?', '#', '?', '#']);
+    }
+
+    #[test]
+    fn orientations() {
+        // an L shape, found upright and on its side
+        let pattern = Pattern::parse("#\n##", ' ');
+        let grid = parse_grid("#...\n##..\n..##\n..#.");
+        let matches = pattern.find(&grid);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].orientation, Orientation::new(false, 0));
+        assert_eq!(matches[0].position, Vector2::new(0, 0));
+        assert_eq!(matches[1].position, Vector2::new(2, 2));
+        assert_eq!(
+            matches[1].cells,
+            vec![Vector2::new(2, 2), Vector2::new(3, 2), Vector2::new(2, 3)]
+        );
+    }
+
+    #[test]
+    fn overlapping_and_symmetric() {
+        // a horizontal pair looks the same flipped, so each place is found once
+        let pattern = Pattern::parse("##", ' ');
+        let grid = parse_grid("###");
+        let matches = pattern.find(&grid);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].position, Vector2::new(1, 0));
+
+        let marked = mark(&grid, &matches[..1], 'O');
+        assert_eq!(marked.data, vec!['O', 'O', '#']);
+    }
+
+    #[test]
+    fn too_big() {
+        let pattern = Pattern::parse("###", ' ');
+        assert!(pattern.find(&parse_grid("##\n##")).is_empty());
+    }
+}